@@ -0,0 +1,161 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use rs_promethee_core::alternatives::AlternativeTable;
+use rs_promethee_core::generalized_criterion::GeneralizedCriterion;
+use rs_promethee_core::PrometheeProblem;
+
+/// System allocator keeping track of the peak of allocated memory
+struct PeakAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAlloc = PeakAlloc;
+
+/// Peak memory allocated while running f, above what was allocated before
+fn peak_memory<F: FnMut()>(mut f: F) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    f();
+    PEAK.load(Ordering::Relaxed) - before
+}
+
+/// Simple linear congruential generator, enough to build reproducible random problems
+struct Lcg(u64);
+
+impl Lcg {
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn random_problem(n: usize, q: usize, seed: u64) -> PrometheeProblem {
+    let mut rng = Lcg(seed);
+    let matrix: Vec<Vec<f64>> = (0..n)
+        .map(|_| (0..q).map(|_| rng.next_f64() * 10e5).collect())
+        .collect();
+    let criteria = (0..q)
+        .map(|_| GeneralizedCriterion::Linear { q: 10e3, p: 10e4 })
+        .collect();
+
+    PrometheeProblem::new(
+        AlternativeTable::from_matrix(matrix),
+        criteria,
+        vec![1.0; q],
+    )
+}
+
+fn random_weights(q: usize, sweeps: usize, seed: u64) -> Vec<Vec<f64>> {
+    let mut rng = Lcg(seed);
+    (0..sweeps)
+        .map(|_| (0..q).map(|_| rng.next_f64() + 1e-3).collect())
+        .collect()
+}
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+fn bench_weight_sweep(n: usize, q: usize, sweeps: usize) {
+    let problem = random_problem(n, q, 42);
+    let weight_sets = random_weights(q, sweeps, 7);
+
+    let naive = time(|| {
+        for weights in &weight_sets {
+            std::hint::black_box(problem.solve_with_weights(weights));
+        }
+    });
+
+    let cached = time(|| {
+        let unicriterion_flows = problem.compute_unicriterion_flows();
+        for weights in &weight_sets {
+            std::hint::black_box(unicriterion_flows.aggregate(weights));
+        }
+    });
+
+    println!(
+        "weight sweep n={} q={} sweeps={}: solve_with_weights {:?}, cached aggregate {:?}",
+        n, q, sweeps, naive, cached
+    );
+}
+
+fn bench_batch(n: usize, q: usize, sweeps: usize) {
+    let problem = random_problem(n, q, 42);
+    let weight_sets = random_weights(q, sweeps, 7);
+
+    let naive = time(|| {
+        for weights in &weight_sets {
+            std::hint::black_box(problem.solve_with_weights(weights));
+        }
+    });
+
+    let batch = time(|| {
+        std::hint::black_box(problem.solve_batch(&weight_sets));
+    });
+
+    println!(
+        "batch n={} q={} sweeps={}: repeated solve {:?}, solve_batch {:?}",
+        n, q, sweeps, naive, batch
+    );
+}
+
+fn bench_single_precision(n: usize) {
+    let mut rng = Lcg(42);
+    let matrix: Vec<Vec<f64>> = (0..n).map(|_| vec![rng.next_f64() * 10e5]).collect();
+    let problem = PrometheeProblem::new(
+        AlternativeTable::from_matrix(matrix),
+        vec![GeneralizedCriterion::UShape { p: 10e4 }],
+        vec![1.0],
+    );
+
+    let mut duration = Duration::ZERO;
+    let memory = peak_memory(|| {
+        duration = time(|| {
+            std::hint::black_box(problem.solve());
+        })
+    });
+    let mut duration_f32 = Duration::ZERO;
+    let memory_f32 = peak_memory(|| {
+        duration_f32 = time(|| {
+            std::hint::black_box(problem.solve_f32());
+        })
+    });
+
+    println!(
+        "pairwise n={}: f64 {:?} peak {} MB, f32 {:?} peak {} MB",
+        n,
+        duration,
+        memory >> 20,
+        duration_f32,
+        memory_f32 >> 20
+    );
+}
+
+fn main() {
+    bench_weight_sweep(1000, 5, 100);
+    bench_weight_sweep(10000, 10, 100);
+    bench_batch(10000, 10, 100);
+    bench_single_precision(5000);
+}
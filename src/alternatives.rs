@@ -1,7 +1,10 @@
+use std::collections::HashSet;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
+use crate::PrometheeError;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Alternative {
     name: String,
@@ -34,7 +37,10 @@ impl Alternative {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Keywords in a criterion name hinting that it should be minimized
+const COST_KEYWORDS: [&str; 6] = ["price", "cost", "prix", "coût", "time", "delay"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OptimizationDirection {
     Min,
     Max,
@@ -52,7 +58,7 @@ impl FromStr for OptimizationDirection {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlternativeTable {
     alternatives: Box<[Alternative]>,
     criteria_names: Box<[Box<str>]>,
@@ -108,6 +114,60 @@ impl AlternativeTable {
         Self::new(alternatives.into_boxed_slice())
     }
 
+    /// Parse a table from whitespace separated text, where each line contains the name of an
+    /// alternative followed by its evaluations. Blank lines and everything after a `#` are ignored.
+    pub fn from_str_matrix(text: &str) -> Result<Self, PrometheeError> {
+        let mut alternatives: Vec<Alternative> = Vec::new();
+
+        for (line_idx, line) in text.lines().enumerate() {
+            let content = match line.split_once('#') {
+                Some((before, _)) => before,
+                None => line,
+            };
+            let mut tokens = content.split_whitespace();
+            let name = match tokens.next() {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let performances = tokens
+                .map(|token| {
+                    token.parse::<f64>().map_err(|_| PrometheeError::Parse {
+                        line: line_idx + 1,
+                        message: format!("Invalid evaluation '{}'", token),
+                    })
+                })
+                .collect::<Result<Vec<f64>, PrometheeError>>()?;
+
+            if performances.is_empty() {
+                return Err(PrometheeError::Parse {
+                    line: line_idx + 1,
+                    message: format!("No evaluation given for alternative '{}'", name),
+                });
+            }
+            if let Some(first) = alternatives.first() {
+                if first.perfs().len() != performances.len() {
+                    return Err(PrometheeError::Parse {
+                        line: line_idx + 1,
+                        message: format!(
+                            "{} evaluations given, {} expected",
+                            performances.len(),
+                            first.perfs().len()
+                        ),
+                    });
+                }
+            }
+
+            alternatives.push(Alternative::new(name.to_string(), performances));
+        }
+
+        if alternatives.is_empty() {
+            return Err(PrometheeError::EmptyTable);
+        }
+
+        Ok(Self::new(alternatives.into_boxed_slice()))
+    }
+
     pub fn with_criteria_directions(
         mut self,
         criteria_direction: Vec<OptimizationDirection>,
@@ -116,6 +176,28 @@ impl AlternativeTable {
         self
     }
 
+    /// Suggest a direction for each criterion from its name: Min if one of its words, split on the
+    /// non-alphanumeric characters, is a cost-like keyword (price, cost, time...) or its plural, Max otherwise.
+    /// "Delivery time" is minimized while "Uptime" is not. The table is left untouched.
+    pub fn infer_directions_by_name(&self) -> Vec<OptimizationDirection> {
+        self.criteria_names
+            .iter()
+            .map(|name| {
+                let name = name.to_lowercase();
+                let is_cost_word = |word: &str| {
+                    COST_KEYWORDS
+                        .iter()
+                        .any(|keyword| word == *keyword || word.strip_suffix('s') == Some(keyword))
+                };
+                if name.split(|c: char| !c.is_alphanumeric()).any(is_cost_word) {
+                    OptimizationDirection::Min
+                } else {
+                    OptimizationDirection::Max
+                }
+            })
+            .collect()
+    }
+
     pub fn set_criterion_direction(&mut self, k: usize, direction: OptimizationDirection) {
         self.criteria_direction[k] = direction;
     }
@@ -143,11 +225,39 @@ impl AlternativeTable {
         }
     }
 
+    /// Put criterion k in the given direction, negating its evaluations only if it was in the other one, so that
+    /// higher evaluations stay the better ones as the rest of the table expects (see `oriented_perf`).
+    /// Unlike `swap_criteria_direction`, calling it several times leaves the evaluations negated at most once.
+    pub fn set_direction(&mut self, k: usize, direction: OptimizationDirection) {
+        if k >= self.criteria_direction.len() {
+            panic!("Invalid criterion index");
+        }
+        if self.criteria_direction[k] != direction {
+            self.swap_criteria_direction(k);
+        }
+    }
+
     pub fn with_criteria_names(mut self, criteria_names: Vec<String>) -> Self {
         self.criteria_names = criteria_names.into_iter().map(|s| s.into()).collect();
         self
     }
 
+    /// Check that the criteria names are unique, returning the first duplicated name otherwise
+    pub fn validate_names(&self) -> Result<(), PrometheeError> {
+        let mut seen: HashSet<&str> = HashSet::new();
+        for name in self.criteria_names.iter() {
+            if !seen.insert(name) {
+                return Err(PrometheeError::DuplicateCriterionName(name.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn has_duplicate_alt_names(&self) -> bool {
+        let mut seen = HashSet::new();
+        !self.alternatives.iter().all(|alt| seen.insert(alt.name()))
+    }
+
     pub fn alternative(&self, i: usize) -> Option<&Alternative> {
         self.alternatives.get(i)
     }
@@ -186,6 +296,76 @@ impl AlternativeTable {
             .collect()
     }
 
+    /// Smallest and largest evaluations on criterion k
+    pub fn criterion_bounds(&self, k: usize) -> Option<(f64, f64)> {
+        self.criterion(k).map(|column| {
+            column
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &f| {
+                    (min.min(f), max.max(f))
+                })
+        })
+    }
+
+    /// Same table with the evaluations of each criterion rescaled linearly to [0, 1].
+    /// Criteria on which all the alternatives have the same value are set to 0.
+    pub fn normalized(&self) -> Self {
+        let mut table = self.clone();
+        for (k, column) in self.criteria().iter().enumerate() {
+            let (min, max) = self.criterion_bounds(k).unwrap();
+            for (alt, f) in table.alternatives.iter_mut().zip(column) {
+                alt.performances[k] = if max > min {
+                    (f - min) / (max - min)
+                } else {
+                    0.0
+                };
+            }
+        }
+        table
+    }
+
+    /// Evaluations of each alternative (rows) rescaled to [0, 1] on each criterion (columns), so that
+    /// 1 is the best value of the column and 0 the worst (see `oriented_perf`), e.g. to draw a radar chart labelled with `criteria_names`.
+    /// As in `normalized`, criteria on which all the alternatives have the same value are set to 0.
+    /// Missing (NaN) evaluations are left out of the bounds and stay NaN, for the chart to skip them.
+    pub fn radar_data(&self) -> Vec<Vec<f64>> {
+        let bounds: Vec<(f64, f64)> = (0..self.q())
+            .map(|k| {
+                (0..self.n())
+                    .map(|i| self.oriented_perf(i, k))
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), f| {
+                        (min.min(f), max.max(f))
+                    })
+            })
+            .collect();
+
+        (0..self.n())
+            .map(|i| {
+                bounds
+                    .iter()
+                    .enumerate()
+                    .map(|(k, &(min, max))| {
+                        if max > min {
+                            (self.oriented_perf(i, k) - min) / (max - min)
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Indices of the criteria whose range of values is at most eps, they cannot discriminate the alternatives
+    pub fn flat_criteria(&self, eps: f64) -> Vec<usize> {
+        (0..self.q())
+            .filter(|&k| {
+                let (min, max) = self.criterion_bounds(k).unwrap();
+                max - min <= eps
+            })
+            .collect()
+    }
+
     pub fn criteria_names(&self) -> &[Box<str>] {
         &self.criteria_names
     }
@@ -211,6 +391,143 @@ impl AlternativeTable {
         }
     }
 
+    /// Apply f to the evaluation of every alternative on criterion k, e.g. a logarithm for a skewed criterion
+    pub fn transform_criterion(&mut self, k: usize, f: impl Fn(f64) -> f64) {
+        if k >= self.q() {
+            panic!("Invalid criterion index");
+        }
+        for alt in self.alternatives.iter_mut() {
+            alt.performances[k] = f(alt.performances[k]);
+        }
+    }
+
+    /// Same table with f applied to the evaluations of criterion k, see `transform_criterion`
+    pub fn with_transformed_criterion(mut self, k: usize, f: impl Fn(f64) -> f64) -> Self {
+        self.transform_criterion(k, f);
+        self
+    }
+
+    /// Round every evaluation to the given number of decimals, so that differences below this precision
+    /// become exact ties. Evaluations with |f| * 10^decimals >= 2^52 have no fractional digit left at this
+    /// precision and are kept as is, and decimals are capped at `f64::MAX_10_EXP`, so that no evaluation
+    /// becomes NaN or infinite.
+    pub fn round_evaluations(&mut self, decimals: u32) {
+        let scale = 10f64.powi(decimals.min(f64::MAX_10_EXP as u32) as i32);
+        for alt in self.alternatives.iter_mut() {
+            for f in alt.performances.iter_mut() {
+                let scaled = *f * scale;
+                // Such a value has no fractional part left to round, or scaling it overflowed
+                if scaled.abs() < (1u64 << 52) as f64 {
+                    *f = scaled.round() / scale;
+                }
+            }
+        }
+    }
+
+    /// Evaluation of alternative i on criterion k, where higher is better. As in `PrometheeProblem::new`,
+    /// the evaluations of Min criteria are expected to be already negated (see `swap_criteria_direction`),
+    /// so the direction is not applied again here.
+    fn oriented_perf(&self, i: usize, k: usize) -> f64 {
+        self.alternatives[i].perfs()[k]
+    }
+
+    /// Return true if alternative a Pareto-dominates alternative b: a is at least as good as b on
+    /// every criterion and strictly better on at least one, higher evaluations being better.
+    /// An alternative with a missing (NaN) evaluation neither dominates nor is dominated.
+    pub fn dominates(&self, a: usize, b: usize) -> bool {
+        let mut strictly_better = false;
+        for k in 0..self.q() {
+            let (fa, fb) = (self.oriented_perf(a, k), self.oriented_perf(b, k));
+            if fa.is_nan() || fb.is_nan() || fa < fb {
+                return false;
+            }
+            strictly_better |= fa > fb;
+        }
+        strictly_better
+    }
+
+    /// Return the indices of the alternatives that dominate alternative i
+    pub fn dominators(&self, i: usize) -> Vec<usize> {
+        (0..self.n()).filter(|&j| self.dominates(j, i)).collect()
+    }
+
+    /// Return, for each alternative, the number of alternatives that dominate it
+    pub fn dominated_by_count(&self) -> Vec<usize> {
+        (0..self.n()).map(|i| self.dominators(i).len()).collect()
+    }
+
+    /// Pearson correlation matrix of size (q, q) between the criteria, higher evaluations being better
+    /// (see `oriented_perf`): two criteria favouring the same alternatives are positively correlated,
+    /// whatever their directions. The correlation with a constant criterion is undefined, it is set to 0
+    /// so that such a criterion is never reported as redundant. The diagonal is always 1.
+    /// Each pair of criteria is correlated over the alternatives with both evaluations, missing (NaN) ones
+    /// being left out.
+    pub fn criterion_correlations(&self) -> Vec<Vec<f64>> {
+        let q = self.q();
+        let correlation = |k: usize, l: usize| -> f64 {
+            let pairs: Vec<(f64, f64)> = (0..self.n())
+                .map(|i| (self.oriented_perf(i, k), self.oriented_perf(i, l)))
+                .filter(|(fk, fl)| !(fk.is_nan() || fl.is_nan()))
+                .collect();
+            if pairs.is_empty() {
+                return 0.0;
+            }
+            let n = pairs.len() as f64;
+            let mean_k = pairs.iter().map(|(fk, _)| fk).sum::<f64>() / n;
+            let mean_l = pairs.iter().map(|(_, fl)| fl).sum::<f64>() / n;
+            // Centered oriented evaluations of both criteria
+            let (mut cov, mut norm_k, mut norm_l) = (0.0, 0.0, 0.0);
+            for (fk, fl) in pairs {
+                let (dk, dl) = (fk - mean_k, fl - mean_l);
+                cov += dk * dl;
+                norm_k += dk * dk;
+                norm_l += dl * dl;
+            }
+            if norm_k == 0.0 || norm_l == 0.0 {
+                0.0
+            } else {
+                cov / (norm_k.sqrt() * norm_l.sqrt())
+            }
+        };
+
+        (0..q)
+            .map(|k| {
+                (0..q)
+                    .map(|l| if k == l { 1.0 } else { correlation(k, l) })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Return, for each alternative, its Pareto layer: layer 0 is the set of non-dominated alternatives,
+    /// layer 1 the non-dominated alternatives once layer 0 is removed, and so on
+    pub fn dominance_layers(&self) -> Vec<usize> {
+        let n = self.n();
+        let mut layers: Vec<Option<usize>> = vec![None; n];
+        let mut layer = 0;
+        while layers.iter().any(Option::is_none) {
+            let front: Vec<usize> = (0..n)
+                .filter(|&i| {
+                    layers[i].is_none()
+                        && !(0..n).any(|j| layers[j].is_none() && self.dominates(j, i))
+                })
+                .collect();
+            if front.is_empty() {
+                // Cannot happen as dominance is acyclic, but never loop forever: the rest is the last layer
+                layers
+                    .iter_mut()
+                    .filter(|l| l.is_none())
+                    .for_each(|l| *l = Some(layer));
+                break;
+            }
+            for i in front {
+                layers[i] = Some(layer);
+            }
+            layer += 1;
+        }
+        layers.into_iter().flatten().collect()
+    }
+
     pub fn n(&self) -> usize {
         self.alternatives.len()
     }
@@ -219,3 +536,329 @@ impl AlternativeTable {
         self.alternatives[0].perfs().len()
     }
 }
+
+#[cfg(feature = "ndarray")]
+impl AlternativeTable {
+    /// Evaluations as an (n, q) matrix
+    pub fn to_array2(&self) -> ndarray::Array2<f64> {
+        ndarray::Array2::from_shape_fn((self.n(), self.q()), |(i, k)| {
+            self.alternatives[i].performances[k]
+        })
+    }
+
+    /// Build a table from an (n, q) matrix of evaluations, the names of the n alternatives and of the q criteria
+    pub fn from_array2(
+        array: ndarray::ArrayView2<f64>,
+        names: Vec<String>,
+        criteria_names: Vec<String>,
+    ) -> Self {
+        let (n, q) = array.dim();
+        if names.len() != n {
+            panic!(
+                "Wrong number of alternative names given, {} given, {} expected",
+                names.len(),
+                n
+            );
+        }
+        if criteria_names.len() != q {
+            panic!(
+                "Wrong number of criteria names given, {} given, {} expected",
+                criteria_names.len(),
+                q
+            );
+        }
+
+        let alternatives: Vec<Alternative> = names
+            .into_iter()
+            .zip(array.rows())
+            .map(|(name, row)| Alternative::new(name, row.to_vec()))
+            .collect();
+
+        Self::new(alternatives.into_boxed_slice()).with_criteria_names(criteria_names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_str_matrix() {
+        let text = "# name price speed
+A 3.0 1.0
+
+B 2 4   # comment after the values
+  C 2.0 3.0
+";
+        let table = AlternativeTable::from_str_matrix(text).unwrap();
+
+        assert_eq!(table.n(), 3);
+        assert_eq!(table.q(), 2);
+        assert_eq!(table.alt_names(), vec!["A", "B", "C"]);
+        assert_eq!(table.criterion(1).unwrap(), vec![1.0, 4.0, 3.0]);
+    }
+
+    #[test]
+    fn parse_str_matrix_errors() {
+        let ragged = AlternativeTable::from_str_matrix("A 1.0 2.0\n\nB 1.0\n");
+        assert!(matches!(ragged, Err(PrometheeError::Parse { line: 3, .. })));
+
+        let not_a_number = AlternativeTable::from_str_matrix("A 1.0 2.0\nB 1.0 x\n");
+        assert!(matches!(
+            not_a_number,
+            Err(PrometheeError::Parse { line: 2, .. })
+        ));
+
+        let empty = AlternativeTable::from_str_matrix("# only a comment\n");
+        assert!(matches!(empty, Err(PrometheeError::EmptyTable)));
+    }
+
+    #[test]
+    fn dominance_chain() {
+        // A dominates B which dominates C, D is incomparable with the others
+        let mut table = AlternativeTable::from_matrix(vec![
+            vec![3.0, 1.0],
+            vec![2.0, 2.0],
+            vec![1.0, 2.0],
+            vec![4.0, 3.0],
+        ]);
+        table.swap_criteria_direction(1);
+
+        assert!(table.dominates(0, 1));
+        assert!(!table.dominates(1, 0));
+        assert_eq!(table.dominators(0), Vec::<usize>::new());
+        assert_eq!(table.dominators(1), vec![0]);
+        assert_eq!(table.dominators(2), vec![0, 1]);
+        assert_eq!(table.dominated_by_count(), vec![0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn set_direction_is_idempotent() {
+        let mut table = AlternativeTable::from_matrix(vec![vec![3.0, 1.0], vec![2.0, 4.0]]);
+
+        for _ in 0..3 {
+            table.set_direction(1, OptimizationDirection::Min);
+            assert_eq!(*table.criterion_direction(1), OptimizationDirection::Min);
+            assert_eq!(table.criterion(1).unwrap(), vec![-1.0, -4.0]);
+        }
+        // The other criterion is left untouched
+        assert_eq!(table.criterion(0).unwrap(), vec![3.0, 2.0]);
+
+        table.set_direction(1, OptimizationDirection::Max);
+        table.set_direction(1, OptimizationDirection::Max);
+        assert_eq!(table.criterion(1).unwrap(), vec![1.0, 4.0]);
+    }
+
+    #[test]
+    fn set_direction_then_dominates() {
+        // Costs, where the first alternative is the cheapest
+        let mut table = AlternativeTable::from_matrix(vec![vec![1.0], vec![4.0]]);
+        table.set_direction(0, OptimizationDirection::Min);
+        table.set_direction(0, OptimizationDirection::Min);
+
+        assert!(table.dominates(0, 1));
+        assert!(!table.dominates(1, 0));
+        assert_eq!(table.dominance_layers(), vec![0, 1]);
+        assert_eq!(table.radar_data(), vec![vec![1.0], vec![0.0]]);
+    }
+
+    #[test]
+    fn radar_data() {
+        let mut table = AlternativeTable::from_matrix(vec![
+            vec![3.0, 1.0, 5.0],
+            vec![2.0, 4.0, 5.0],
+            vec![1.0, 3.0, 5.0],
+        ]);
+        table.swap_criteria_direction(1);
+
+        let radar = table.radar_data();
+        assert_eq!(radar[0], vec![1.0, 1.0, 0.0]);
+        assert_eq!(radar[1], vec![0.5, 0.0, 0.0]);
+        assert!((radar[2][1] - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(radar[2][0], 0.0);
+    }
+
+    #[test]
+    fn transform_criterion() {
+        let table = AlternativeTable::from_matrix(vec![vec![1.0, 2.0], vec![100.0, 3.0]])
+            .with_transformed_criterion(0, f64::log10);
+        assert_eq!(table.criterion(0).unwrap(), vec![0.0, 2.0]);
+        assert_eq!(table.criterion(1).unwrap(), vec![2.0, 3.0]);
+    }
+
+    #[test]
+    fn round_evaluations() {
+        let mut table = AlternativeTable::from_matrix(vec![vec![1.004, -2.5], vec![0.996, 12.345]]);
+        table.round_evaluations(2);
+        assert_eq!(table.criterion(0).unwrap(), vec![1.0, 1.0]);
+        assert_eq!(table.criterion(1).unwrap(), vec![-2.5, 12.35]);
+
+        // Large values and decimals are left unchanged instead of overflowing to NaN or infinity
+        let values = vec![vec![1e300, 0.0], vec![-3.25, 1.5e-7]];
+        let mut table = AlternativeTable::from_matrix(values.clone());
+        table.round_evaluations(u32::MAX);
+        assert_eq!(table.criterion(0).unwrap(), vec![1e300, -3.25]);
+        assert_eq!(table.criterion(1).unwrap(), vec![0.0, 1.5e-7]);
+        table.round_evaluations(400);
+        assert!(table
+            .criterion(0)
+            .unwrap()
+            .into_iter()
+            .chain(table.criterion(1).unwrap())
+            .all(f64::is_finite));
+    }
+
+    #[test]
+    fn criterion_correlations() {
+        // The second criterion is an affine function of the first one, the third one decreases with
+        // the first one but is minimized, the fourth one is constant
+        let mut table = AlternativeTable::from_matrix(vec![
+            vec![1.0, 5.0, 10.0, 2.0],
+            vec![2.0, 7.0, 8.0, 2.0],
+            vec![4.0, 11.0, 4.0, 2.0],
+        ]);
+        table.swap_criteria_direction(2);
+
+        let correlations = table.criterion_correlations();
+        assert!((correlations[0][1] - 1.0).abs() < 1e-12);
+        assert!((correlations[1][0] - 1.0).abs() < 1e-12);
+        assert!((correlations[0][2] - 1.0).abs() < 1e-12);
+        assert_eq!(correlations[0][3], 0.0);
+        assert_eq!(correlations[3][3], 1.0);
+    }
+
+    #[test]
+    fn missing_values_in_radar_data_and_correlations() {
+        let table = AlternativeTable::from_matrix(vec![
+            vec![1.0, 5.0, 3.0],
+            vec![2.0, f64::NAN, 1.0],
+            vec![4.0, 11.0, 2.0],
+            vec![3.0, 9.0, f64::NAN],
+        ]);
+
+        let radar = table.radar_data();
+        assert_eq!(radar[0][0], 0.0);
+        assert_eq!(radar[2][1], 1.0);
+        assert!(radar[1][1].is_nan());
+        assert!(radar
+            .iter()
+            .flatten()
+            .filter(|f| !f.is_nan())
+            .all(|f| (0.0..=1.0).contains(f)));
+
+        // The first two criteria are affinely related on the alternatives where both are given
+        let correlations = table.criterion_correlations();
+        assert!((correlations[0][1] - 1.0).abs() < 1e-12);
+        assert!(correlations.iter().flatten().all(|c| c.is_finite()));
+    }
+
+    #[test]
+    fn dominance_layers() {
+        // A and D form the Pareto front, B and E are dominated only by the front, C is dominated by B
+        let mut table = AlternativeTable::from_matrix(vec![
+            vec![3.0, 1.0],
+            vec![2.0, 2.0],
+            vec![1.0, 2.0],
+            vec![4.0, 3.0],
+            vec![3.5, 3.5],
+        ]);
+        table.swap_criteria_direction(1);
+
+        assert_eq!(table.dominance_layers(), vec![0, 1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn dominance_with_missing_values() {
+        let table = AlternativeTable::from_matrix(vec![
+            vec![f64::NAN, 2.0],
+            vec![1.0, 1.0],
+            vec![2.0, f64::NAN],
+            vec![0.0, 0.0],
+        ]);
+
+        assert!(!table.dominates(0, 1));
+        assert!(!table.dominates(1, 0));
+        assert!(table.dominates(1, 3));
+        assert_eq!(table.dominance_layers(), vec![0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn duplicate_names() {
+        let table = AlternativeTable::from_str_matrix("A 1 2 3\nB 2 3 4\nA 3 4 5").unwrap();
+        assert!(table.has_duplicate_alt_names());
+        assert_eq!(table.validate_names(), Ok(()));
+
+        let table = table.with_criteria_names(vec![
+            "Price".to_string(),
+            "Speed".to_string(),
+            "Price".to_string(),
+        ]);
+        assert_eq!(
+            table.validate_names(),
+            Err(PrometheeError::DuplicateCriterionName("Price".to_string()))
+        );
+
+        let table = AlternativeTable::from_str_matrix("A 1 2\nB 2 3").unwrap();
+        assert!(!table.has_duplicate_alt_names());
+    }
+
+    #[test]
+    fn flat_criteria() {
+        let table = AlternativeTable::from_matrix(vec![
+            vec![1.0, 5.0, 2.0],
+            vec![2.0, 5.0, 2.05],
+            vec![3.0, 5.0, 2.0],
+        ]);
+
+        assert_eq!(table.flat_criteria(0.0), vec![1]);
+        assert_eq!(table.flat_criteria(0.1), vec![1, 2]);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn array2_round_trip() {
+        let table =
+            AlternativeTable::from_matrix(vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]])
+                .with_criteria_names(vec!["Price".to_string(), "Speed".to_string()]);
+
+        let array = table.to_array2();
+        assert_eq!(array.dim(), (3, 2));
+        assert_eq!(array[[2, 0]], 5.0);
+
+        let names = table.alt_names().iter().map(|s| s.to_string()).collect();
+        let criteria_names = table
+            .criteria_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let restored = AlternativeTable::from_array2(array.view(), names, criteria_names);
+
+        assert_eq!(restored.alt_names(), table.alt_names());
+        assert_eq!(restored.criteria_names(), table.criteria_names());
+        assert_eq!(restored.criteria(), table.criteria());
+    }
+
+    #[test]
+    fn infer_directions_from_names() {
+        let table = AlternativeTable::from_matrix(vec![vec![1.0, 2.0, 3.0, 4.0, 5.0]])
+            .with_criteria_names(vec![
+                "Prix".to_string(),
+                "Vitesse".to_string(),
+                "Delivery time".to_string(),
+                "Uptime".to_string(),
+                "Shipping-costs".to_string(),
+            ]);
+
+        assert_eq!(
+            table.infer_directions_by_name(),
+            vec![
+                OptimizationDirection::Min,
+                OptimizationDirection::Max,
+                OptimizationDirection::Min,
+                OptimizationDirection::Max,
+                OptimizationDirection::Min
+            ]
+        );
+        assert_eq!(table.criterion_direction(0), &OptimizationDirection::Max);
+    }
+}
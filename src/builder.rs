@@ -0,0 +1,387 @@
+use crate::alternatives::{Alternative, AlternativeTable, OptimizationDirection};
+use crate::generalized_criterion::GeneralizedCriterion;
+use crate::{FlowNormalization, PrometheeError, PrometheeProblem};
+
+/// Criterion declared in a builder
+#[derive(Debug, Clone)]
+struct CriterionSpec {
+    name: String,
+    direction: OptimizationDirection,
+    generalized_criterion: GeneralizedCriterion,
+    weight: f64,
+}
+
+/// Assemble a problem criterion by criterion and alternative by alternative.
+/// Mistakes are reported as soon as the faulty criterion or alternative is added.
+#[derive(Debug, Clone, Default)]
+pub struct PrometheeProblemBuilder {
+    criteria: Vec<CriterionSpec>,
+    alternatives: Vec<Alternative>,
+    preserve_signs: bool,
+    flow_normalization: FlowNormalization,
+    allow_missing: bool,
+    /// Directions declared by the source of the problem, see `PrometheeProblem::with_declared_directions`
+    declared_directions: Vec<Option<OptimizationDirection>>,
+}
+
+impl PrometheeProblemBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare the next criterion, criteria must all be declared before the alternatives
+    pub fn criterion(
+        mut self,
+        name: &str,
+        direction: OptimizationDirection,
+        generalized_criterion: GeneralizedCriterion,
+        weight: f64,
+    ) -> Result<Self, PrometheeError> {
+        if self.criteria.iter().any(|spec| spec.name == name) {
+            return Err(PrometheeError::DuplicateCriterionName(name.to_string()));
+        }
+        if let Some(alt) = self.alternatives.first() {
+            return Err(PrometheeError::AlternativePerfCountMismatch {
+                alt: alt.name().to_string(),
+                expected: self.criteria.len() + 1,
+                got: alt.perfs().len(),
+            });
+        }
+
+        self.criteria.push(CriterionSpec {
+            name: name.to_string(),
+            direction,
+            generalized_criterion,
+            weight,
+        });
+        Ok(self)
+    }
+
+    /// Add an alternative with one evaluation per declared criterion
+    pub fn alternative(mut self, name: &str, perfs: Vec<f64>) -> Result<Self, PrometheeError> {
+        if self.criteria.is_empty() {
+            return Err(PrometheeError::NoCriteria);
+        }
+        if perfs.len() != self.criteria.len() {
+            return Err(PrometheeError::AlternativePerfCountMismatch {
+                alt: name.to_string(),
+                expected: self.criteria.len(),
+                got: perfs.len(),
+            });
+        }
+
+        self.alternatives
+            .push(Alternative::new(name.to_string(), perfs));
+        Ok(self)
+    }
+
+    /// Change the weight of a declared criterion
+    pub fn weight(mut self, name: &str, weight: f64) -> Result<Self, PrometheeError> {
+        self.criteria
+            .iter_mut()
+            .find(|spec| spec.name == name)
+            .ok_or_else(|| PrometheeError::UnknownCriterion(name.to_string()))?
+            .weight = weight;
+        Ok(self)
+    }
+
+    /// Change the preference function of a declared criterion
+    pub fn preference_function(
+        mut self,
+        name: &str,
+        generalized_criterion: GeneralizedCriterion,
+    ) -> Result<Self, PrometheeError> {
+        self.criteria
+            .iter_mut()
+            .find(|spec| spec.name == name)
+            .ok_or_else(|| PrometheeError::UnknownCriterion(name.to_string()))?
+            .generalized_criterion = generalized_criterion;
+        Ok(self)
+    }
+
+    /// See `PrometheeProblem::new_preserve_signs`. Without it, the evaluations of the Min criteria are
+    /// negated when building the problem, they are given as is in any case.
+    pub fn preserve_signs(mut self, preserve_signs: bool) -> Self {
+        self.preserve_signs = preserve_signs;
+        self
+    }
+
+    /// Accept NaN evaluations as missing values instead of rejecting them, see `PrometheeProblem`
+    pub fn allow_missing(mut self, allow_missing: bool) -> Self {
+        self.allow_missing = allow_missing;
+        self
+    }
+
+    pub fn flow_normalization(mut self, flow_normalization: FlowNormalization) -> Self {
+        self.flow_normalization = flow_normalization;
+        self
+    }
+
+    /// Build the problem, the evaluations of each Min criterion being negated unless `preserve_signs`
+    /// so that it is minimized either way
+    pub fn build(self) -> Result<PrometheeProblem, PrometheeError> {
+        if self.criteria.is_empty() {
+            return Err(PrometheeError::NoCriteria);
+        }
+        if self.alternatives.is_empty() {
+            return Err(PrometheeError::EmptyTable);
+        }
+
+        let (names, directions): (Vec<String>, Vec<OptimizationDirection>) = self
+            .criteria
+            .iter()
+            .map(|spec| (spec.name.clone(), spec.direction.clone()))
+            .unzip();
+        let mut alt_table =
+            AlternativeTable::new(self.alternatives.into_boxed_slice()).with_criteria_names(names);
+        if self.preserve_signs {
+            alt_table = alt_table.with_criteria_directions(directions);
+        } else {
+            // The criteria start as Max, set_direction negates the evaluations of the Min ones
+            for (k, direction) in directions.into_iter().enumerate() {
+                alt_table.set_direction(k, direction);
+            }
+        }
+
+        let (generalized_criteria, weights) = self
+            .criteria
+            .into_iter()
+            .map(|spec| (spec.generalized_criterion, spec.weight))
+            .unzip();
+        PrometheeProblem::try_build(
+            alt_table,
+            generalized_criteria,
+            weights,
+            self.preserve_signs,
+            self.allow_missing,
+        )
+        .map(|problem| {
+            let problem = problem.with_flow_normalization(self.flow_normalization);
+            if self.declared_directions.is_empty() {
+                problem
+            } else {
+                problem.with_declared_directions(self.declared_directions)
+            }
+        })
+    }
+}
+
+impl PrometheeProblem {
+    /// Builder pre-filled with the alternatives, criteria and settings of this problem, to edit it
+    /// and build a new one. The weights are the normalized ones, which the builder normalizes again.
+    /// The evaluations of the Min criteria are given back as is, the builder negating them again if needed.
+    pub fn to_builder(&self) -> PrometheeProblemBuilder {
+        let mut alternatives = self.alt_table.alternatives().to_vec();
+        if !self.preserve_signs {
+            for k in (0..self.q)
+                .filter(|&k| *self.alt_table.criterion_direction(k) == OptimizationDirection::Min)
+            {
+                for alt in alternatives.iter_mut() {
+                    let val = alt.perf(k).unwrap();
+                    alt.change_perf(k, -val);
+                }
+            }
+        }
+
+        PrometheeProblemBuilder {
+            criteria: (0..self.q)
+                .map(|k| CriterionSpec {
+                    name: self.criterion_name(k).unwrap().to_string(),
+                    direction: self.alt_table.criterion_direction(k).clone(),
+                    generalized_criterion: self.generalized_criteria[k].clone(),
+                    weight: self.weights[k],
+                })
+                .collect(),
+            alternatives,
+            preserve_signs: self.preserve_signs,
+            flow_normalization: self.flow_normalization,
+            allow_missing: (0..self.q).any(|k| self.has_missing(k)),
+            declared_directions: self.declared_directions.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price_and_speed() -> PrometheeProblemBuilder {
+        PrometheeProblemBuilder::new()
+            .criterion(
+                "Price",
+                OptimizationDirection::Min,
+                GeneralizedCriterion::VShape { p: 3.0 },
+                3.0,
+            )
+            .unwrap()
+            .criterion(
+                "Speed",
+                OptimizationDirection::Max,
+                GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+                7.0,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn build_problem() {
+        let problem = price_and_speed()
+            .alternative("A", vec![3.0, 1.0])
+            .unwrap()
+            .alternative("B", vec![2.0, 4.0])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(problem.n(), 2);
+        assert_eq!(problem.criterion_name(1), Some("Speed"));
+        assert!((problem.w(0).unwrap() - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn duplicate_criterion() {
+        let err = price_and_speed()
+            .criterion(
+                "Price",
+                OptimizationDirection::Min,
+                GeneralizedCriterion::Usual,
+                1.0,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            PrometheeError::DuplicateCriterionName("Price".to_string())
+        );
+    }
+
+    #[test]
+    fn alternative_perf_count_mismatch() {
+        let err = price_and_speed().alternative("A", vec![3.0]).unwrap_err();
+        assert_eq!(
+            err,
+            PrometheeError::AlternativePerfCountMismatch {
+                alt: "A".to_string(),
+                expected: 2,
+                got: 1
+            }
+        );
+
+        // Declaring a criterion after the alternatives would leave them short of an evaluation
+        let err = price_and_speed()
+            .alternative("A", vec![3.0, 1.0])
+            .unwrap()
+            .criterion(
+                "Comfort",
+                OptimizationDirection::Max,
+                GeneralizedCriterion::Usual,
+                1.0,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            PrometheeError::AlternativePerfCountMismatch {
+                expected: 3,
+                got: 2,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn no_criteria() {
+        assert_eq!(
+            PrometheeProblemBuilder::new().build().unwrap_err(),
+            PrometheeError::NoCriteria
+        );
+        assert_eq!(
+            PrometheeProblemBuilder::new()
+                .alternative("A", vec![])
+                .unwrap_err(),
+            PrometheeError::NoCriteria
+        );
+        assert_eq!(
+            price_and_speed().build().unwrap_err(),
+            PrometheeError::EmptyTable
+        );
+    }
+
+    #[test]
+    fn to_builder_round_trip() {
+        let problem = price_and_speed()
+            .alternative("A", vec![3.0, 1.0])
+            .unwrap()
+            .alternative("B", vec![2.0, 4.0])
+            .unwrap()
+            .alternative("C", vec![2.0, 3.0])
+            .unwrap()
+            .preserve_signs(true)
+            .build()
+            .unwrap();
+
+        let rebuilt = problem.to_builder().build().unwrap();
+        assert_eq!(rebuilt.alt_names(), problem.alt_names());
+        assert_eq!(rebuilt.criteria_names(), problem.criteria_names());
+        assert_eq!(rebuilt.solve().net_flows(), problem.solve().net_flows());
+
+        let edited = problem
+            .to_builder()
+            .weight("Price", 1.0)
+            .unwrap()
+            .weight("Speed", 0.0)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(edited.w(0), Some(&1.0));
+        assert_eq!(
+            problem.to_builder().weight("Comfort", 1.0).unwrap_err(),
+            PrometheeError::UnknownCriterion("Comfort".to_string())
+        );
+    }
+
+    #[test]
+    fn min_criteria_are_minimized() {
+        let build = |preserve_signs: bool| {
+            price_and_speed()
+                .alternative("A", vec![3.0, 1.0])
+                .unwrap()
+                .alternative("B", vec![2.0, 1.0])
+                .unwrap()
+                .preserve_signs(preserve_signs)
+                .build()
+                .unwrap()
+        };
+
+        // B is cheaper and as fast as A
+        let negated = build(false);
+        let preserved = build(true);
+        assert_eq!(negated.solve().ranked_alts(), vec![1, 0]);
+        assert_eq!(negated.solve().net_flows(), preserved.solve().net_flows());
+    }
+
+    #[test]
+    fn to_builder_keeps_evaluations_and_declared_directions() {
+        let problem = price_and_speed()
+            .alternative("A", vec![3.0, 1.0])
+            .unwrap()
+            .alternative("B", vec![2.0, 4.0])
+            .unwrap()
+            .alternative("C", vec![2.0, 3.0])
+            .unwrap()
+            .build()
+            .unwrap()
+            .with_declared_directions(vec![Some(OptimizationDirection::Min), None]);
+
+        let builder = problem.to_builder();
+        assert_eq!(builder.alternatives[0].perfs(), &[3.0, 1.0]);
+        let rebuilt = builder.build().unwrap();
+        assert_eq!(rebuilt.solve().net_flows(), problem.solve().net_flows());
+        assert_eq!(
+            rebuilt.check_direction_consistency(),
+            problem.check_direction_consistency()
+        );
+        assert_eq!(
+            rebuilt.to_builder().declared_directions,
+            vec![Some(OptimizationDirection::Min), None]
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+use crate::alternatives::AlternativeTable;
+use crate::PrometheeProblem;
+
+/// Rule deciding the category of an alternative whose net flow equals the one of a boundary profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssignmentRule {
+    /// The alternative goes to the category below the boundary
+    Pessimistic,
+    /// The alternative goes to the category above the boundary
+    Optimistic,
+}
+
+/// FlowSort classification of alternatives into ordered categories separated by boundary profiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlowSort {
+    /// Boundary profiles, from the worst to the best one
+    profiles: AlternativeTable,
+    /// Labels of the categories, from the worst to the best one, one more than the profiles
+    labels: Vec<String>,
+    rule: AssignmentRule,
+}
+
+impl FlowSort {
+    pub fn new(profiles: AlternativeTable, labels: Vec<String>, rule: AssignmentRule) -> Self {
+        if labels.len() != profiles.n() + 1 {
+            panic!(
+                "Wrong number of category labels given, {} given, {} expected",
+                labels.len(),
+                profiles.n() + 1
+            );
+        }
+
+        Self {
+            profiles,
+            labels,
+            rule,
+        }
+    }
+
+    pub fn n_categories(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn label(&self, c: usize) -> Option<&str> {
+        self.labels.get(c).map(|s| s.as_str())
+    }
+
+    /// Category index of each alternative of the problem, from 0 for the worst category.
+    /// Each alternative is compared to the profiles through the net flows computed over the set
+    /// made of the profiles and this alternative only.
+    pub fn classify(&self, problem: &PrometheeProblem) -> Vec<usize> {
+        let n_ref = self.profiles.n() as f64;
+        let (positive_flows, negative_flows) = problem.flows_against(&self.profiles);
+
+        // Preferences among the profiles, which do not depend on the classified alternative
+        let profiles_pi = PrometheeProblem::build(
+            self.profiles.clone(),
+            problem.generalized_criteria.clone(),
+            problem.weights.clone(),
+            problem.preserve_signs,
+        )
+        .preference_matrix();
+        let profiles_net: Vec<f64> = (0..self.profiles.n())
+            .map(|h| {
+                (0..self.profiles.n())
+                    .map(|r| profiles_pi[h][r] - profiles_pi[r][h])
+                    .sum()
+            })
+            .collect();
+
+        (0..problem.n())
+            .map(|i| {
+                let net_flow = positive_flows[i] - negative_flows[i];
+                (0..self.profiles.n())
+                    .filter(|&h| {
+                        let (pi_ah, pi_ha) = self.preferences_with_profile(problem, i, h);
+                        let profile_net_flow = (profiles_net[h] + pi_ha - pi_ah) / n_ref;
+                        match self.rule {
+                            AssignmentRule::Pessimistic => profile_net_flow < net_flow,
+                            AssignmentRule::Optimistic => profile_net_flow <= net_flow,
+                        }
+                    })
+                    .count()
+            })
+            .collect()
+    }
+
+    /// Preference degrees (pi(a_i, r_h), pi(r_h, a_i)) between an alternative and a profile
+    fn preferences_with_profile(
+        &self,
+        problem: &PrometheeProblem,
+        i: usize,
+        h: usize,
+    ) -> (f64, f64) {
+        (0..problem.q())
+            .map(|k| {
+                let d = problem.criterion_sign(k)
+                    * (problem.perf(k, i).unwrap() - self.profiles.performance(h, k).unwrap());
+                let criterion = &problem.generalized_criteria[k];
+                (
+                    problem.weights[k] * criterion.normalisation(d),
+                    problem.weights[k] * criterion.loss_normalisation(d),
+                )
+            })
+            .fold((0.0, 0.0), |(acc_ah, acc_ha), (ah, ha)| {
+                (acc_ah + ah, acc_ha + ha)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generalized_criterion::GeneralizedCriterion;
+
+    #[test]
+    fn two_boundaries_three_categories() {
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![0.0], vec![1.5], vec![2.0], vec![5.0]]),
+            vec![GeneralizedCriterion::Usual],
+            vec![1.0],
+        );
+        let profiles = AlternativeTable::from_matrix(vec![vec![1.0], vec![2.0]]);
+        let labels = vec!["Bad".to_string(), "Medium".to_string(), "Good".to_string()];
+
+        let pessimistic = FlowSort::new(
+            profiles.clone(),
+            labels.clone(),
+            AssignmentRule::Pessimistic,
+        );
+        let optimistic = FlowSort::new(profiles, labels, AssignmentRule::Optimistic);
+
+        // The third alternative has the same net flow as the upper boundary
+        assert_eq!(pessimistic.classify(&problem), vec![0, 1, 1, 2]);
+        assert_eq!(optimistic.classify(&problem), vec![0, 1, 2, 2]);
+        assert_eq!(pessimistic.label(2), Some("Good"));
+    }
+}
@@ -0,0 +1,257 @@
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Promethee2Result, PrometheeProblem};
+
+/// Maximal number of power iterations to find a principal axis
+const MAX_ITERATIONS: usize = 1000;
+/// Change of the axis between two power iterations under which it is considered converged
+const CONVERGENCE_EPS: f64 = 1e-12;
+
+/// GAIA plane: projection of the alternatives, described by their unicriterion net flows, on the plane
+/// of the two principal axes of these flows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GaiaPlane {
+    /// Coordinates (x, y) of each alternative
+    pub alternatives: Vec<(f64, f64)>,
+    /// End point (x, y) of the axis of each criterion, the projection of its unit vector
+    pub criteria: Vec<(f64, f64)>,
+    /// Projection of the weights vector, pointing towards the best alternatives
+    pub decision_stick: (f64, f64),
+    /// Share of the variance of the unicriterion net flows kept by the plane, in [0, 1]
+    pub delta: f64,
+}
+
+impl GaiaPlane {
+    /// Compute the plane from the unicriterion net flows of a result and the weights of the criteria.
+    /// With a single criterion, or when the flows vary along a single direction, the y coordinates are all 0.
+    pub fn new(result: &Promethee2Result, weights: &[f64]) -> Self {
+        let q = result.unicrit_positive_flows.len();
+        if weights.len() != q {
+            panic!(
+                "Wrong number of weights given, {} given, {} expected",
+                weights.len(),
+                q
+            );
+        }
+        let n = result.positive_flows.len();
+        let profiles: Vec<Vec<f64>> = (0..n).map(|i| result.profile(i).unwrap()).collect();
+
+        // Covariance matrix of the unicriterion net flows, which are centered on each criterion
+        let covariance: Vec<Vec<f64>> = (0..q)
+            .map(|k| {
+                (0..q)
+                    .map(|l| profiles.iter().map(|phi| phi[k] * phi[l]).sum::<f64>() / n as f64)
+                    .collect()
+            })
+            .collect();
+        let trace: f64 = (0..q).map(|k| covariance[k][k]).sum();
+
+        let (u, lambda_u) = principal_axis(&covariance, &[]);
+        let (v, lambda_v) = principal_axis(&covariance, &[&u]);
+        let project = |x: &[f64]| -> (f64, f64) {
+            (
+                x.iter().zip(&u).map(|(a, b)| a * b).sum(),
+                x.iter().zip(&v).map(|(a, b)| a * b).sum(),
+            )
+        };
+
+        Self {
+            alternatives: profiles.iter().map(|phi| project(phi)).collect(),
+            criteria: (0..q).map(|k| (u[k], v[k])).collect(),
+            decision_stick: project(weights),
+            delta: if trace > 0.0 {
+                (lambda_u + lambda_v) / trace
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Write the coordinates as CSV, in three sections each starting with a `# ` line and a header:
+    /// the alternatives named with names, the end points of the criteria axes named with criteria_names
+    /// and the decision stick. Names are quoted as needed.
+    pub fn to_csv<W: Write>(
+        &self,
+        w: W,
+        names: &[&str],
+        criteria_names: &[&str],
+    ) -> io::Result<()> {
+        for (what, given, expected) in [
+            ("names", names.len(), self.alternatives.len()),
+            ("criteria names", criteria_names.len(), self.criteria.len()),
+        ] {
+            if given != expected {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Wrong number of {} given, {} given, {} expected",
+                        what, given, expected
+                    ),
+                ));
+            }
+        }
+
+        let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(w);
+        writer.write_record(["# alternatives"])?;
+        writer.write_record(["name", "x", "y"])?;
+        for (name, (x, y)) in names.iter().zip(&self.alternatives) {
+            writer.write_record([name.to_string(), x.to_string(), y.to_string()])?;
+        }
+        writer.write_record(["# criteria"])?;
+        writer.write_record(["criterion", "x", "y"])?;
+        for (name, (x, y)) in criteria_names.iter().zip(&self.criteria) {
+            writer.write_record([name.to_string(), x.to_string(), y.to_string()])?;
+        }
+        writer.write_record(["# decision stick"])?;
+        writer.write_record(["x", "y"])?;
+        writer.write_record([
+            self.decision_stick.0.to_string(),
+            self.decision_stick.1.to_string(),
+        ])?;
+        writer.flush()
+    }
+}
+
+impl PrometheeProblem {
+    /// GAIA plane of the solution of the problem, see `GaiaPlane::new`
+    pub fn gaia_plane(&self) -> GaiaPlane {
+        GaiaPlane::new(&self.solve(), &self.weights)
+    }
+}
+
+/// Unit eigenvector of the symmetric positive semi-definite matrix with the largest eigenvalue among the
+/// ones orthogonal to the given axes, and its eigenvalue, found by power iteration.
+/// The zero vector is returned if the matrix is zero in every such direction.
+fn principal_axis(matrix: &[Vec<f64>], orthogonal_to: &[&[f64]]) -> (Vec<f64>, f64) {
+    let q = matrix.len();
+    let orthogonalize = |x: &mut Vec<f64>| {
+        for axis in orthogonal_to {
+            let dot: f64 = x.iter().zip(axis.iter()).map(|(a, b)| a * b).sum();
+            x.iter_mut()
+                .zip(axis.iter())
+                .for_each(|(a, b)| *a -= dot * b);
+        }
+        let norm = x.iter().map(|a| a * a).sum::<f64>().sqrt();
+        if norm > CONVERGENCE_EPS {
+            x.iter_mut().for_each(|a| *a /= norm);
+            true
+        } else {
+            false
+        }
+    };
+
+    // Deterministic start, not orthogonal to any eigenvector in practice
+    let mut x: Vec<f64> = (0..q).map(|k| 1.0 + k as f64 / q as f64).collect();
+    if !orthogonalize(&mut x) {
+        return (vec![0.0; q], 0.0);
+    }
+    for _ in 0..MAX_ITERATIONS {
+        let mut next: Vec<f64> = matrix
+            .iter()
+            .map(|row| row.iter().zip(&x).map(|(a, b)| a * b).sum())
+            .collect();
+        if !orthogonalize(&mut next) {
+            return (vec![0.0; q], 0.0);
+        }
+        let change: f64 = next.iter().zip(&x).map(|(a, b)| (a - b).abs()).sum();
+        x = next;
+        if change < CONVERGENCE_EPS {
+            break;
+        }
+    }
+
+    // Orient the axis so that its components sum to a non-negative value, for reproducible signs
+    if x.iter().sum::<f64>() < 0.0 {
+        x.iter_mut().for_each(|a| *a = -*a);
+    }
+    let lambda = matrix
+        .iter()
+        .zip(&x)
+        .map(|(row, xk)| xk * row.iter().zip(&x).map(|(a, b)| a * b).sum::<f64>())
+        .sum();
+    (x, lambda)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alternatives::AlternativeTable;
+    use crate::generalized_criterion::GeneralizedCriterion;
+
+    fn init_problem() -> PrometheeProblem {
+        PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![
+                vec![3.0, 1.0, 2.0],
+                vec![2.0, 4.0, 2.0],
+                vec![2.0, 3.0, 5.0],
+                vec![1.0, 2.0, 3.0],
+            ]),
+            vec![
+                GeneralizedCriterion::VShape { p: 3.0 },
+                GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+                GeneralizedCriterion::Usual,
+            ],
+            vec![3.0, 7.0, 2.0],
+        )
+    }
+
+    #[test]
+    fn plane_of_two_criteria_keeps_all_the_variance() {
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![3.0, 1.0], vec![2.0, 4.0], vec![2.0, 3.0]]),
+            vec![
+                GeneralizedCriterion::VShape { p: 3.0 },
+                GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+            ],
+            vec![3.0, 7.0],
+        );
+        let result = problem.solve();
+        let plane = problem.gaia_plane();
+
+        assert!((plane.delta - 1.0).abs() < 1e-9);
+        // A rotation of the plane keeps the distances between the alternatives
+        let distances = result.profile_distance_matrix();
+        for (a, &(xa, ya)) in plane.alternatives.iter().enumerate() {
+            for (b, &(xb, yb)) in plane.alternatives.iter().enumerate() {
+                assert!(((xa - xb).hypot(ya - yb) - distances[a][b]).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn to_csv_sections() {
+        let problem = init_problem();
+        let plane = problem.gaia_plane();
+        assert!(plane.delta > 0.0 && plane.delta <= 1.0 + 1e-12);
+
+        let mut buffer = Vec::new();
+        plane
+            .to_csv(
+                &mut buffer,
+                &["A", "B, the second", "C", "D"],
+                &["k0", "k1", "k2"],
+            )
+            .unwrap();
+        let csv = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 2 + 4 + 2 + 3 + 2 + 1);
+        assert_eq!(lines[0], "# alternatives");
+        assert_eq!(lines[1], "name,x,y");
+        assert!(lines[2].starts_with("A,"));
+        assert!(lines[3].starts_with("\"B, the second\","));
+        assert_eq!(lines[6], "# criteria");
+        assert!(lines[8].starts_with("k0,"));
+        assert_eq!(lines[11], "# decision stick");
+        assert_eq!(lines[13].split(',').count(), 2);
+
+        assert!(plane
+            .to_csv(Vec::new(), &["A"], &["k0", "k1", "k2"])
+            .is_err());
+        assert!(plane
+            .to_csv(Vec::new(), &["A", "B", "C", "D"], &["k0"])
+            .is_err());
+    }
+}
@@ -1,15 +1,39 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GeneralizedCriterion {
-    UShape { p: f64 },
-    VShape { p: f64 },
-    Linear { q: f64, p: f64 },
+    UShape {
+        p: f64,
+    },
+    VShape {
+        p: f64,
+    },
+    Linear {
+        q: f64,
+        p: f64,
+    },
     Usual,
+    /// Behavioral extension where a gain and a loss of the same amount are not perceived alike:
+    /// a difference in favour of the alternative follows the Linear ramp (q_pos, p_pos) and a difference
+    /// against it the ramp (q_neg, p_neg). The positive flow of an alternative aggregates its gains and
+    /// its negative flow its losses, so the flows no longer come from a single preference matrix and are
+    /// always computed pairwise. `normalisation` only gives the gain ramp, use `sym_normalisation` or
+    /// `loss_normalisation` for the losses.
+    AsymmetricLinear {
+        q_pos: f64,
+        p_pos: f64,
+        q_neg: f64,
+        p_neg: f64,
+    },
 }
 
 impl GeneralizedCriterion {
+    /// Preference degree of an alternative whose evaluation exceeds the other one by d_ij.
+    /// A NaN difference, when one of the evaluations is missing, gives no preference.
     pub fn normalisation(&self, d_ij: f64) -> f64 {
+        if d_ij.is_nan() {
+            return 0.0;
+        }
         match *self {
             GeneralizedCriterion::VShape { p } => normalize_v_shape(p, d_ij),
             GeneralizedCriterion::Linear { q, p } => normalize_linear(q, p, d_ij),
@@ -27,29 +51,124 @@ impl GeneralizedCriterion {
                     1.0
                 }
             }
+            GeneralizedCriterion::AsymmetricLinear { q_pos, p_pos, .. } => {
+                normalize_linear(q_pos, p_pos, d_ij)
+            }
+        }
+    }
+
+    /// Preference degree counted as a loss for an alternative whose evaluation differs by d_ij
+    /// from the other one. This is `normalisation(-d_ij)` for all criteria but `AsymmetricLinear`,
+    /// which uses its loss ramp.
+    pub fn loss_normalisation(&self, d_ij: f64) -> f64 {
+        match *self {
+            GeneralizedCriterion::AsymmetricLinear { .. } => {
+                (-self.sym_normalisation(d_ij)).max(0.0)
+            }
+            _ => self.normalisation(-d_ij),
+        }
+    }
+
+    /// Check that the thresholds are meaningful: p > 0 for the U-Shape and V-Shape criteria,
+    /// 0 <= q < p for the Linear one. With p = 0 the preference degree would be undefined.
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            GeneralizedCriterion::UShape { p } | GeneralizedCriterion::VShape { p }
+                if p.is_nan() || p <= 0.0 =>
+            {
+                Err(format!("Threshold p must be positive, got {}", p))
+            }
+            GeneralizedCriterion::Linear { q, p }
+                if q.is_nan() || p.is_nan() || q < 0.0 || p <= q =>
+            {
+                Err(format!(
+                    "Thresholds must satisfy 0 <= q < p, got q = {} and p = {}",
+                    q, p
+                ))
+            }
+            GeneralizedCriterion::AsymmetricLinear {
+                q_pos,
+                p_pos,
+                q_neg,
+                p_neg,
+            } => GeneralizedCriterion::Linear { q: q_pos, p: p_pos }
+                .validate()
+                .and(GeneralizedCriterion::Linear { q: q_neg, p: p_neg }.validate()),
+            _ => Ok(()),
+        }
+    }
+
+    /// V-Shape criterion with threshold p, or the Usual criterion, its limit, if p is not positive
+    pub fn v_shape_or_usual(p: f64) -> GeneralizedCriterion {
+        if p > 0.0 {
+            GeneralizedCriterion::VShape { p }
+        } else {
+            GeneralizedCriterion::Usual
+        }
+    }
+
+    /// Same criterion for evaluations rescaled from a range of old_range to a range of new_range,
+    /// the thresholds are scaled proportionally
+    pub fn rescale(&self, old_range: f64, new_range: f64) -> GeneralizedCriterion {
+        let factor = new_range / old_range;
+        match *self {
+            GeneralizedCriterion::UShape { p } => GeneralizedCriterion::UShape { p: p * factor },
+            GeneralizedCriterion::VShape { p } => GeneralizedCriterion::VShape { p: p * factor },
+            GeneralizedCriterion::Linear { q, p } => GeneralizedCriterion::Linear {
+                q: q * factor,
+                p: p * factor,
+            },
+            GeneralizedCriterion::Usual => GeneralizedCriterion::Usual,
+            GeneralizedCriterion::AsymmetricLinear {
+                q_pos,
+                p_pos,
+                q_neg,
+                p_neg,
+            } => GeneralizedCriterion::AsymmetricLinear {
+                q_pos: q_pos * factor,
+                p_pos: p_pos * factor,
+                q_neg: q_neg * factor,
+                p_neg: p_neg * factor,
+            },
         }
     }
 
     pub fn sym_normalisation(&self, d_ij: f64) -> f64 {
-        d_ij.signum()
-            * match *self {
-                GeneralizedCriterion::VShape { p } => normalize_v_shape(p, d_ij.abs()),
-                GeneralizedCriterion::Linear { q, p } => normalize_linear(q, p, d_ij.abs()),
-                GeneralizedCriterion::Usual => {
-                    if d_ij != 0.0 {
-                        1.0
-                    } else {
-                        0.0
-                    }
+        if d_ij.is_nan() {
+            return 0.0;
+        }
+        match *self {
+            GeneralizedCriterion::AsymmetricLinear {
+                q_pos,
+                p_pos,
+                q_neg,
+                p_neg,
+            } => {
+                if d_ij >= 0.0 {
+                    normalize_linear(q_pos, p_pos, d_ij)
+                } else {
+                    -normalize_linear(q_neg, p_neg, -d_ij)
                 }
-                GeneralizedCriterion::UShape { p } => {
-                    if d_ij.abs() < p {
-                        0.0
-                    } else {
-                        1.0
-                    }
+            }
+            GeneralizedCriterion::VShape { p } => d_ij.signum() * normalize_v_shape(p, d_ij.abs()),
+            GeneralizedCriterion::Linear { q, p } => {
+                d_ij.signum() * normalize_linear(q, p, d_ij.abs())
+            }
+            GeneralizedCriterion::Usual => {
+                if d_ij != 0.0 {
+                    d_ij.signum()
+                } else {
+                    0.0
                 }
             }
+            GeneralizedCriterion::UShape { p } => {
+                if d_ij.abs() < p {
+                    0.0
+                } else {
+                    d_ij.signum()
+                }
+            }
+        }
     }
 }
 
@@ -75,12 +194,62 @@ fn normalize_v_shape(p: f64, d_ij: f64) -> f64 {
 
 pub fn from_params(ftype: &str, q: f64, p: f64) -> GeneralizedCriterion {
     match ftype {
-        "Usual" => GeneralizedCriterion::Usual,
-        "U-Shape" => GeneralizedCriterion::UShape { p },
-        "V-Shape" => GeneralizedCriterion::VShape { p },
-        "Linear" => GeneralizedCriterion::Linear { q, p },
         "Level" | "Gaussian" => unimplemented!(),
-        _ => panic!("Wrong type"),
+        _ => try_from_params(ftype, q, p).expect("Wrong type"),
+    }
+}
+
+/// Same as `from_params`, but returns an error instead of panicking on an unknown or unsupported type
+pub fn try_from_params(ftype: &str, q: f64, p: f64) -> Result<GeneralizedCriterion, String> {
+    match ftype {
+        "Usual" => Ok(GeneralizedCriterion::Usual),
+        "U-Shape" => Ok(GeneralizedCriterion::UShape { p }),
+        "V-Shape" => Ok(GeneralizedCriterion::VShape { p }),
+        "Linear" => Ok(GeneralizedCriterion::Linear { q, p }),
+        "Level" | "Gaussian" => Err(format!("Preference function '{}' not implemented", ftype)),
+        _ => Err(format!("Unknown preference function '{}'", ftype)),
+    }
+}
+
+/// Localized names of the preference functions: (locale, name, canonical name)
+const LOCALIZED_NAMES: [(&str, &str, &str); 9] = [
+    ("fr", "Usuel", "Usual"),
+    ("fr", "Forme en U", "U-Shape"),
+    ("fr", "U", "U-Shape"),
+    ("fr", "Forme en V", "V-Shape"),
+    ("fr", "V", "V-Shape"),
+    ("fr", "Linéaire", "Linear"),
+    ("fr", "Lineaire", "Linear"),
+    ("fr", "Niveau", "Level"),
+    ("fr", "Gaussien", "Gaussian"),
+];
+
+/// Canonical name of a preference function given by its canonical or localized name, case-insensitively.
+/// Localized names are looked up in the given locale only, or in every locale if None.
+pub fn canonical_name(ftype: &str, locale: Option<&str>) -> Option<&'static str> {
+    let ftype = ftype.trim();
+    ["Usual", "U-Shape", "V-Shape", "Linear", "Level", "Gaussian"]
+        .into_iter()
+        .find(|name| name.eq_ignore_ascii_case(ftype))
+        .or_else(|| {
+            LOCALIZED_NAMES
+                .iter()
+                .filter(|(loc, _, _)| locale.is_none_or(|locale| locale == *loc))
+                .find(|(_, name, _)| name.to_lowercase() == ftype.to_lowercase())
+                .map(|(_, _, canonical)| *canonical)
+        })
+}
+
+/// Same as `try_from_params`, also accepting localized names of the preference functions, case-insensitively
+pub fn try_from_params_localized(
+    ftype: &str,
+    locale: Option<&str>,
+    q: f64,
+    p: f64,
+) -> Result<GeneralizedCriterion, String> {
+    match canonical_name(ftype, locale) {
+        Some(canonical) => try_from_params(canonical, q, p),
+        None => Err(format!("Unknown preference function '{}'", ftype)),
     }
 }
 
@@ -121,4 +290,85 @@ mod test_generalized_normalisation {
         assert_eq!(b, 0.5);
         assert_eq!(c, 1.0);
     }
+
+    #[test]
+    fn validate_thresholds() {
+        use super::GeneralizedCriterion;
+
+        assert!(GeneralizedCriterion::VShape { p: 0.0 }.validate().is_err());
+        assert!(GeneralizedCriterion::UShape { p: -1.0 }.validate().is_err());
+        assert!(GeneralizedCriterion::Linear { q: 2.0, p: 2.0 }
+            .validate()
+            .is_err());
+        assert!(GeneralizedCriterion::VShape { p: f64::NAN }
+            .validate()
+            .is_err());
+        assert!(GeneralizedCriterion::Linear { q: 0.0, p: 2.0 }
+            .validate()
+            .is_ok());
+        assert!(GeneralizedCriterion::Usual.validate().is_ok());
+
+        assert_eq!(
+            GeneralizedCriterion::v_shape_or_usual(0.0),
+            GeneralizedCriterion::Usual
+        );
+    }
+
+    #[test]
+    fn french_names() {
+        use super::{try_from_params_localized, GeneralizedCriterion};
+
+        let fr = Some("fr");
+        assert_eq!(
+            try_from_params_localized("Usuel", fr, 0.0, 0.0),
+            Ok(GeneralizedCriterion::Usual)
+        );
+        assert_eq!(
+            try_from_params_localized("linéaire", fr, 1.0, 2.0),
+            Ok(GeneralizedCriterion::Linear { q: 1.0, p: 2.0 })
+        );
+        assert_eq!(
+            try_from_params_localized("V", None, 0.0, 2.0),
+            Ok(GeneralizedCriterion::VShape { p: 2.0 })
+        );
+        assert_eq!(
+            try_from_params_localized("u-shape", fr, 0.0, 2.0),
+            Ok(GeneralizedCriterion::UShape { p: 2.0 })
+        );
+        assert_eq!(
+            try_from_params_localized("Usuel", Some("de"), 0.0, 0.0),
+            Err("Unknown preference function 'Usuel'".to_string())
+        );
+        // Known but not implemented yet
+        assert!(try_from_params_localized("Niveau", fr, 0.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn asymmetric_linear() {
+        use super::GeneralizedCriterion;
+
+        let linear = GeneralizedCriterion::Linear { q: 1.0, p: 3.0 };
+        let symmetric = GeneralizedCriterion::AsymmetricLinear {
+            q_pos: 1.0,
+            p_pos: 3.0,
+            q_neg: 1.0,
+            p_neg: 3.0,
+        };
+        for d in [-4.0, -2.0, -0.5, 0.0, 0.5, 2.0, 4.0] {
+            assert_eq!(symmetric.normalisation(d), linear.normalisation(d));
+            assert_eq!(symmetric.sym_normalisation(d), linear.sym_normalisation(d));
+            assert_eq!(symmetric.loss_normalisation(d), linear.normalisation(-d));
+        }
+
+        let loss_averse = GeneralizedCriterion::AsymmetricLinear {
+            q_pos: 1.0,
+            p_pos: 3.0,
+            q_neg: 0.0,
+            p_neg: 2.0,
+        };
+        assert_eq!(loss_averse.normalisation(2.0), 0.5);
+        assert_eq!(loss_averse.sym_normalisation(-1.0), -0.5);
+        assert_eq!(loss_averse.loss_normalisation(-1.0), 0.5);
+        assert!(loss_averse.validate().is_ok());
+    }
 }
@@ -0,0 +1,235 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::alternatives::AlternativeTable;
+use crate::generalized_criterion::GeneralizedCriterion;
+use crate::{ranking_kendall_tau, Promethee2Result, PrometheeProblem};
+
+/// Promethee problem shared by several decision-makers, each having their own weights on the criteria
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupProblem {
+    alt_table: AlternativeTable,
+    generalized_criteria: Vec<GeneralizedCriterion>,
+    /// For each decision-maker, its weight in the group and its weights on the criteria
+    decision_makers: Vec<(f64, Vec<f64>)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupResult {
+    /// Flows aggregated over the decision-makers, weighted by their (normalized) weight in the group
+    pub consensus: Promethee2Result,
+    /// Flows of each decision-maker, in the order they were given
+    pub individual: Vec<Promethee2Result>,
+}
+
+impl GroupResult {
+    pub fn consensus_net_flows(&self) -> Vec<f64> {
+        self.consensus.net_flows()
+    }
+
+    /// Return the alternatives ranked in descending order of consensus net flow
+    pub fn consensus_ranking(&self) -> Vec<usize> {
+        self.consensus.ranked_alts()
+    }
+
+    pub fn dm_net_flows(&self, d: usize) -> Option<Vec<f64>> {
+        self.individual.get(d).map(|res| res.net_flows())
+    }
+
+    /// Return the alternatives ranked in descending order of net flow for decision-maker d
+    pub fn dm_ranking(&self, d: usize) -> Option<Vec<usize>> {
+        self.individual.get(d).map(|res| res.ranked_alts())
+    }
+
+    pub fn dm_rankings(&self) -> Vec<Vec<usize>> {
+        self.individual
+            .iter()
+            .map(|res| res.ranked_alts())
+            .collect()
+    }
+}
+
+impl GroupProblem {
+    pub fn new(
+        alt_table: AlternativeTable,
+        generalized_criteria: Vec<GeneralizedCriterion>,
+        decision_makers: Vec<(f64, Vec<f64>)>,
+    ) -> Self {
+        if decision_makers.is_empty() {
+            panic!("No decision-maker given");
+        }
+
+        let q = alt_table.q();
+        if let Some((d, (_, weights))) = decision_makers
+            .iter()
+            .enumerate()
+            .find(|(_, (_, weights))| weights.len() != q)
+        {
+            panic!(
+                "Wrong number of weights given for decision-maker {}, {} given, {} expected",
+                d,
+                weights.len(),
+                q
+            );
+        }
+
+        Self {
+            alt_table,
+            generalized_criteria,
+            decision_makers,
+        }
+    }
+
+    pub fn n_decision_makers(&self) -> usize {
+        self.decision_makers.len()
+    }
+
+    pub fn alt_table(&self) -> &AlternativeTable {
+        &self.alt_table
+    }
+
+    pub fn decision_maker(&self, d: usize) -> Option<&(f64, Vec<f64>)> {
+        self.decision_makers.get(d)
+    }
+
+    /// Compute the flows of each decision-maker and their consensus.
+    /// The unicriterion flows do not depend on the weights, so they are computed only once
+    /// and then re-weighted for each decision-maker.
+    pub fn solve(&self) -> GroupResult {
+        let q = self.alt_table.q();
+        let n = self.alt_table.n();
+
+        let unicriterion_flows = PrometheeProblem::new(
+            self.alt_table.clone(),
+            self.generalized_criteria.clone(),
+            vec![1.0; q],
+        )
+        .compute_unicriterion_flows();
+
+        let individual: Vec<Promethee2Result> = self
+            .decision_makers
+            .iter()
+            .map(|(_, weights)| unicriterion_flows.aggregate(weights))
+            .collect();
+
+        let tot_dm_w: f64 = self.decision_makers.iter().map(|(dm_w, _)| dm_w).sum();
+        let mut positive_flows = vec![0.0; n];
+        let mut negative_flows = vec![0.0; n];
+        for ((dm_w, _), res) in self.decision_makers.iter().zip(&individual) {
+            for i in 0..n {
+                positive_flows[i] += dm_w / tot_dm_w * res.positive_flows[i];
+                negative_flows[i] += dm_w / tot_dm_w * res.negative_flows[i];
+            }
+        }
+
+        GroupResult {
+            consensus: Promethee2Result {
+                positive_flows,
+                unicrit_positive_flows: unicriterion_flows.unicrit_positive_flows,
+                negative_flows,
+                unicrit_negative_flows: unicriterion_flows.unicrit_negative_flows,
+            },
+            individual,
+        }
+    }
+
+    /// Agreement between the rankings of the decision-makers, in [0, 1]: the Kendall tau averaged over
+    /// all the pairs of decision-makers, rescaled so that 1 means identical rankings and 0 reversed ones.
+    /// A low degree means that the consensus ranking hides disagreements. 1 with a single decision-maker.
+    pub fn consensus_degree(&self) -> f64 {
+        let rankings = self.solve().dm_rankings();
+        if rankings.len() < 2 {
+            return 1.0;
+        }
+
+        let taus: Vec<f64> = rankings
+            .iter()
+            .tuple_combinations()
+            .map(|(ranking_a, ranking_b)| ranking_kendall_tau(ranking_a, ranking_b))
+            .collect();
+        let mean_tau = taus.iter().sum::<f64>() / taus.len() as f64;
+        (mean_tau + 1.0) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alternatives::Alternative;
+
+    fn init_table() -> AlternativeTable {
+        AlternativeTable::new(
+            vec![
+                Alternative::new("A".to_string(), vec![3.0, 1.0]),
+                Alternative::new("B".to_string(), vec![2.0, 4.0]),
+                Alternative::new("C".to_string(), vec![2.0, 3.0]),
+            ]
+            .into(),
+        )
+    }
+
+    fn init_criteria() -> Vec<GeneralizedCriterion> {
+        vec![
+            GeneralizedCriterion::VShape { p: 3.0 },
+            GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+        ]
+    }
+
+    #[test]
+    fn individual_flows_match_single_problems() {
+        let dms = vec![(1.0, vec![3.0, 7.0]), (2.0, vec![1.0, 0.0])];
+        let group = GroupProblem::new(init_table(), init_criteria(), dms.clone());
+        let result = group.solve();
+
+        for (d, (_, weights)) in dms.into_iter().enumerate() {
+            let single = PrometheeProblem::new(init_table(), init_criteria(), weights).solve();
+            let dm_flows = result.dm_net_flows(d).unwrap();
+            single
+                .net_flows()
+                .iter()
+                .zip(&dm_flows)
+                .for_each(|(a, b)| assert!((a - b).abs() < 1e-9));
+        }
+    }
+
+    #[test]
+    fn consensus_is_weighted_average() {
+        let group = GroupProblem::new(
+            init_table(),
+            init_criteria(),
+            vec![(1.0, vec![3.0, 7.0]), (3.0, vec![1.0, 0.0])],
+        );
+        let result = group.solve();
+        let consensus = result.consensus_net_flows();
+        let flows_0 = result.dm_net_flows(0).unwrap();
+        let flows_1 = result.dm_net_flows(1).unwrap();
+
+        for i in 0..3 {
+            assert!((consensus[i] - (0.25 * flows_0[i] + 0.75 * flows_1[i])).abs() < 1e-9);
+        }
+
+        // The first decision-maker prefers B, the second one A, and the second has more weight
+        assert_eq!(result.dm_ranking(0).unwrap()[0], 1);
+        assert_eq!(result.dm_ranking(1).unwrap()[0], 0);
+        assert_eq!(result.consensus_ranking()[0], 0);
+    }
+
+    #[test]
+    fn consensus_degree() {
+        // Both decision-makers only care about the second criterion
+        let agreeing = GroupProblem::new(
+            init_table(),
+            init_criteria(),
+            vec![(1.0, vec![0.0, 1.0]), (2.0, vec![0.0, 3.0])],
+        );
+        assert_eq!(agreeing.consensus_degree(), 1.0);
+
+        // The second criterion is the opposite of the first one, each decision-maker only cares about one
+        let reversed = GroupProblem::new(
+            AlternativeTable::from_matrix(vec![vec![1.0, -1.0], vec![4.0, -4.0], vec![3.0, -3.0]]),
+            vec![GeneralizedCriterion::Usual, GeneralizedCriterion::Usual],
+            vec![(1.0, vec![1.0, 0.0]), (1.0, vec![0.0, 1.0])],
+        );
+        assert_eq!(reversed.consensus_degree(), 0.0);
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// Criteria organized into groups, with weights given for the groups and for the criteria within each group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriteriaHierarchy {
+    /// Name and weight of each group
+    groups: Vec<(String, f64)>,
+    /// For each criterion, in the order of the problem, the index of its group and its weight within the group
+    criteria: Vec<(usize, f64)>,
+}
+
+impl CriteriaHierarchy {
+    pub fn new(groups: Vec<(String, f64)>) -> Self {
+        Self {
+            groups,
+            criteria: Vec::new(),
+        }
+    }
+
+    /// Add the next criterion of the problem to the given group, with its weight within the group
+    pub fn with_criterion(mut self, group: &str, weight: f64) -> Self {
+        let g = self
+            .groups
+            .iter()
+            .position(|(name, _)| name == group)
+            .unwrap_or_else(|| panic!("Unknown group of criteria '{}'", group));
+        self.criteria.push((g, weight));
+        self
+    }
+
+    pub fn q(&self) -> usize {
+        self.criteria.len()
+    }
+
+    pub fn group_of(&self, k: usize) -> Option<&str> {
+        self.criteria
+            .get(k)
+            .map(|&(g, _)| self.groups[g].0.as_str())
+    }
+
+    /// Effective weight of each criterion: the weight of its group times its weight normalized within
+    /// the group, normalized so that they sum to one. This can be given to `PrometheeProblem::new`.
+    pub fn flat_weights(&self) -> Vec<f64> {
+        let mut within_group_tot = vec![0.0; self.groups.len()];
+        for &(g, w) in &self.criteria {
+            within_group_tot[g] += w;
+        }
+
+        let weights: Vec<f64> = self
+            .criteria
+            .iter()
+            .map(|&(g, w)| self.groups[g].1 * w / within_group_tot[g])
+            .collect();
+        let tot_w: f64 = weights.iter().sum();
+        weights.iter().map(|w| w / tot_w).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_groups_flat_weights() {
+        let hierarchy = CriteriaHierarchy::new(vec![
+            ("Cost".to_string(), 2.0),
+            ("Performance".to_string(), 3.0),
+        ])
+        .with_criterion("Cost", 3.0)
+        .with_criterion("Performance", 1.0)
+        .with_criterion("Cost", 1.0)
+        .with_criterion("Performance", 1.0);
+
+        assert_eq!(hierarchy.q(), 4);
+        assert_eq!(hierarchy.group_of(2), Some("Cost"));
+
+        let expected = [0.4 * 0.75, 0.6 * 0.5, 0.4 * 0.25, 0.6 * 0.5];
+        for (w, e) in hierarchy.flat_weights().iter().zip(expected) {
+            assert!((w - e).abs() < 1e-12);
+        }
+    }
+}
@@ -1,19 +1,238 @@
 pub mod alternatives;
+pub mod builder;
+pub mod flowsort;
+pub mod gaia;
 pub mod generalized_criterion;
+pub mod group;
+pub mod hierarchy;
+pub mod session;
+pub mod solved;
+pub mod weights;
 
 #[cfg(feature = "parse")]
 pub mod parse;
 
 use itertools::Itertools;
 use std::collections::VecDeque;
-use tabled;
+use std::fmt;
 use tabled::settings::Style;
 
 use alternatives::{AlternativeTable, OptimizationDirection};
 use generalized_criterion::GeneralizedCriterion;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Maximal difference between two flows for them to be considered equal, so that differences
+/// coming from rounding errors do not produce a strict order
+pub const FLOW_EPS: f64 = 1e-12;
+
+/// Return true if the flows a and b differ by at most `FLOW_EPS`
+pub fn flows_equal(a: f64, b: f64) -> bool {
+    (a - b).abs() <= FLOW_EPS
+}
+
+/// Spearman rank correlation between two rankings of the same alternatives, each given as the
+/// alternatives from the first to the last. 1 if they are identical, -1 if one is the reverse of the other.
+/// The rankings are strict, tied alternatives get the successive positions they are given in.
+pub fn ranking_spearman(ranking_a: &[usize], ranking_b: &[usize]) -> f64 {
+    let n = ranking_a.len();
+    if ranking_b.len() != n {
+        panic!(
+            "Rankings of different lengths, {} and {} given",
+            n,
+            ranking_b.len()
+        );
+    }
+    if n < 2 {
+        return 1.0;
+    }
+
+    // Scores decreasing along each ranking, so that no alternatives are tied
+    let scores = |ranking: &[usize]| {
+        let mut scores = vec![0.0; n];
+        for (pos, &i) in ranking.iter().enumerate() {
+            scores[i] = -(pos as f64);
+        }
+        scores
+    };
+    flows_spearman(&scores(ranking_a), &scores(ranking_b))
+}
+
+/// Spearman rank correlation between the rankings of the same alternatives in descending order of the flows
+/// flows_a and flows_b. Tied alternatives (see `flows_equal`) share the average of their ranks, and the
+/// correlation is the Pearson one of these ranks. It is 0 if all the alternatives are tied in either ranking.
+pub fn flows_spearman(flows_a: &[f64], flows_b: &[f64]) -> f64 {
+    if flows_a.len() != flows_b.len() {
+        panic!(
+            "Flows of different lengths, {} and {} given",
+            flows_a.len(),
+            flows_b.len()
+        );
+    }
+    let (ranks_a, ranks_b) = (average_ranks(flows_a), average_ranks(flows_b));
+
+    let mean = (flows_a.len() as f64 + 1.0) / 2.0;
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (r_a, r_b) in ranks_a.iter().zip(&ranks_b) {
+        cov += (r_a - mean) * (r_b - mean);
+        var_a += (r_a - mean) * (r_a - mean);
+        var_b += (r_b - mean) * (r_b - mean);
+    }
+    if var_a == 0.0 || var_b == 0.0 {
+        0.0
+    } else {
+        cov / (var_a * var_b).sqrt()
+    }
+}
+
+/// Kendall tau between two rankings of the same alternatives, given as in `ranking_spearman`: the share of
+/// pairs of alternatives ordered the same way minus the share of pairs ordered differently, in [-1, 1]
+pub fn ranking_kendall_tau(ranking_a: &[usize], ranking_b: &[usize]) -> f64 {
+    let n = ranking_a.len();
+    if ranking_b.len() != n {
+        panic!(
+            "Rankings of different lengths, {} and {} given",
+            n,
+            ranking_b.len()
+        );
+    }
+    if n < 2 {
+        return 1.0;
+    }
+
+    let mut position_b = vec![0; n];
+    for (pos, &i) in ranking_b.iter().enumerate() {
+        position_b[i] = pos;
+    }
+    // ranking_a lists the pairs (i, j) with i before j, they are concordant if i is also before j in ranking_b
+    let concordant = ranking_a
+        .iter()
+        .tuple_combinations()
+        .filter(|&(&i, &j)| position_b[i] < position_b[j])
+        .count() as f64;
+    let pairs = (n * (n - 1) / 2) as f64;
+    (2.0 * concordant - pairs) / pairs
+}
+
+/// Smallest sum of weights that can be normalized
+const MIN_WEIGHT_SUM: f64 = 1e-12;
+
+/// Smallest number of alternatives for the flows to be defined
+const MIN_ALTERNATIVES: usize = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrometheeError {
+    /// No alternative was given
+    EmptyTable,
+    /// Invalid textual input, line numbers start at 1
+    Parse { line: usize, message: String },
+    /// Several criteria have the same name
+    DuplicateCriterionName(String),
+    /// The thresholds of the preference function of a criterion are invalid
+    InvalidThresholds { criterion: usize, message: String },
+    /// The weights sum to zero or almost, so they cannot be normalized
+    DegenerateWeights { sum: f64 },
+    /// No criterion was declared
+    NoCriteria,
+    /// No criterion has this name
+    UnknownCriterion(String),
+    /// An alternative does not have one evaluation per declared criterion
+    AlternativePerfCountMismatch {
+        alt: String,
+        expected: usize,
+        got: usize,
+    },
+    /// The length of a part of the problem does not match the number of criteria or alternatives
+    DimensionMismatch {
+        field: String,
+        given: usize,
+        expected: usize,
+    },
+    /// Not enough alternatives to compare them
+    TooFewAlternatives { given: usize, min: usize },
+    /// An evaluation is NaN or infinite
+    NonFiniteEvaluation { alt: String, criterion: usize },
+}
+
+impl fmt::Display for PrometheeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrometheeError::EmptyTable => write!(f, "Empty table of alternatives"),
+            PrometheeError::Parse { line, message } => {
+                write!(f, "Parse error at line {}: {}", line, message)
+            }
+            PrometheeError::DuplicateCriterionName(name) => {
+                write!(f, "Duplicate criterion name '{}'", name)
+            }
+            PrometheeError::InvalidThresholds { criterion, message } => {
+                write!(f, "Invalid criterion {}: {}", criterion, message)
+            }
+            PrometheeError::DegenerateWeights { sum } => {
+                write!(f, "Weights sum to {}, they cannot be normalized", sum)
+            }
+            PrometheeError::NoCriteria => write!(f, "No criterion declared"),
+            PrometheeError::UnknownCriterion(name) => write!(f, "Unknown criterion '{}'", name),
+            PrometheeError::AlternativePerfCountMismatch { alt, expected, got } => write!(
+                f,
+                "Alternative '{}' has {} evaluations, {} expected",
+                alt, got, expected
+            ),
+            PrometheeError::DimensionMismatch {
+                field,
+                given,
+                expected,
+            } => write!(
+                f,
+                "Wrong number of {}, {} given, {} expected",
+                field, given, expected
+            ),
+            PrometheeError::TooFewAlternatives { given, min } => write!(
+                f,
+                "{} alternatives given, at least {} are needed",
+                given, min
+            ),
+            PrometheeError::NonFiniteEvaluation { alt, criterion } => write!(
+                f,
+                "Non finite evaluation of alternative '{}' on criterion {}",
+                alt, criterion
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PrometheeError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RankingError {
+    /// The net flow of an alternative is NaN or infinite, along with the first criterion whose
+    /// unicriterion flows are not finite for this alternative, if any
+    NonFiniteFlow {
+        alternative: usize,
+        criterion: Option<usize>,
+    },
+}
+
+impl fmt::Display for RankingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RankingError::NonFiniteFlow {
+                alternative,
+                criterion: Some(k),
+            } => write!(
+                f,
+                "Non finite net flow for alternative {}, caused by criterion {}",
+                alternative, k
+            ),
+            RankingError::NonFiniteFlow {
+                alternative,
+                criterion: None,
+            } => write!(f, "Non finite net flow for alternative {}", alternative),
+        }
+    }
+}
+
+impl std::error::Error for RankingError {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Promethee2Result {
     pub positive_flows: Vec<f64>,
     pub unicrit_positive_flows: Vec<Vec<f64>>,
@@ -54,59 +273,103 @@ impl Promethee2Result {
         }
     }
 
-    /// Return arguments corresponding to the alternatives, ranked in descending order of preference
-    pub fn ranked_alts(&self) -> Vec<usize> {
-        self.net_flows()
+    /// Net flows shifted so that the net flow of the reference alternative is zero, to compare runs with
+    /// different sets of alternatives that share this reference
+    pub fn relative_to(&self, ref_index: usize) -> Vec<f64> {
+        let reference = self.net_flow(ref_index).unwrap_or_else(|| {
+            panic!(
+                "Reference alternative out of range, {} given, {} alternatives",
+                ref_index,
+                self.positive_flows.len()
+            )
+        });
+        self.net_flows().iter().map(|f| f - reference).collect()
+    }
+
+    /// Unicriterion net flows of alternative ai on each criterion, of length q
+    pub fn profile(&self, ai: usize) -> Option<Vec<f64>> {
+        if ai >= self.positive_flows.len() {
+            None
+        } else {
+            Some(
+                (0..self.unicrit_positive_flows.len())
+                    .map(|k| self.unicriterion_net_flow(k, ai).unwrap())
+                    .collect(),
+            )
+        }
+    }
+
+    /// Matrix of size (n, n) of the Euclidean distances between the profiles of the alternatives,
+    /// close alternatives behave similarly on every criterion whatever their net flows
+    pub fn profile_distance_matrix(&self) -> Vec<Vec<f64>> {
+        let n = self.positive_flows.len();
+        let profiles: Vec<Vec<f64>> = (0..n).map(|ai| self.profile(ai).unwrap()).collect();
+        profiles
             .iter()
-            .enumerate()
-            .sorted_by(|flow_i, flow_j| PartialOrd::partial_cmp(flow_i.1, flow_j.1).unwrap())
-            .map(|(i, _)| i)
-            .rev()
+            .map(|pa| {
+                profiles
+                    .iter()
+                    .map(|pb| {
+                        pa.iter()
+                            .zip(pb)
+                            .map(|(fa, fb)| (fa - fb).powi(2))
+                            .sum::<f64>()
+                            .sqrt()
+                    })
+                    .collect()
+            })
             .collect()
     }
 
-    pub fn is_better(&self, a1: usize, a2: usize) -> bool {
-        self.positive_flows[a1] - self.negative_flows[a1]
-            > self.positive_flows[a2] - self.negative_flows[a2]
+    /// Return arguments corresponding to the alternatives, ranked in descending order of preference.
+    /// Alternatives whose net flows are equal up to `FLOW_EPS` are tied, and ordered by ascending index:
+    /// among tied alternatives, the one given first in the problem comes first.
+    pub fn ranked_alts(&self) -> Vec<usize> {
+        rank_with_ties(&self.net_flows(), |i, j| i.cmp(j))
     }
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PrometheeProblem {
-    n: usize,
-    q: usize,
-    /// Matrix of criteria evaluations of size (q, n)
-    alt_table: AlternativeTable,
-    /// For each criterion, the Option may contain the indices of the alternatives sorted in ascending order of evaluations
-    argsorted_eval_matrix: Vec<Option<Vec<usize>>>,
-    generalized_criteria: Vec<GeneralizedCriterion>,
-    weights: Vec<f64>,
-}
 
-impl PrometheeProblem {
-    pub fn new(
-        alt_table: AlternativeTable,
-        generalized_criteria: Vec<GeneralizedCriterion>,
-        mut weights: Vec<f64>,
-    ) -> Self {
-        // normalize weights
-        let tot_w: f64 = weights.iter().sum();
-        weights = weights.into_iter().map(|w| w / tot_w).collect();
+    /// Groups of tied alternatives, from the best to the worst one. Alternatives whose net flows differ
+    /// by at most eps from the previous one of the ranking are in the same group, ordered by ascending index.
+    pub fn ranked_groups(&self, eps: f64) -> Vec<Vec<usize>> {
+        tied_groups(&self.net_flows(), eps, |i, j| i.cmp(j))
+    }
 
-        // TODO todo!("Use optimization direction somehow");
+    /// Same order as `ranked_groups`, each alternative along with its relation to the previous one,
+    /// e.g. to display the ranks "1. A, 2. B, 2. C, 4. D" when B and C are tied
+    pub fn ranked_with_relations(&self, eps: f64) -> Vec<(usize, RankRelation)> {
+        self.ranked_groups(eps)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(g, group)| {
+                group.into_iter().enumerate().map(move |(pos, i)| {
+                    let relation = match (g, pos) {
+                        (0, 0) => RankRelation::First,
+                        (_, 0) => RankRelation::WorseThanPrevious,
+                        _ => RankRelation::TiedWithPrevious,
+                    };
+                    (i, relation)
+                })
+            })
+            .collect()
+    }
 
-        let n = alt_table.n();
-        let q = alt_table.q();
+    /// Alternatives ranked in descending order of positive (leaving) flow, ties ordered as in `ranked_alts`
+    pub fn ranked_by_positive_flow(&self) -> Vec<usize> {
+        rank_with_ties(&self.positive_flows, |i, j| i.cmp(j))
+    }
 
-        // Verify validity of inputs
-        if generalized_criteria.len() != q {
-            panic!(
-                "Wrong number of generalized criteria given, {} given, {} expected",
-                generalized_criteria.len(),
-                q
-            );
-        }
+    /// Alternatives ranked in ascending order of negative (entering) flow, the least dominated first,
+    /// ties ordered as in `ranked_alts`
+    pub fn ranked_by_negative_flow(&self) -> Vec<usize> {
+        let opposite_flows: Vec<f64> = self.negative_flows.iter().map(|f| -f).collect();
+        rank_with_ties(&opposite_flows, |i, j| i.cmp(j))
+    }
 
+    /// Matrix of size (n, q) of the unicriterion net flows multiplied by the weights, normalized so that
+    /// they sum to one. Each row sums to the net flow of the alternative when the weights are the ones
+    /// used to compute this result.
+    pub fn weighted_unicriterion_net_flows(&self, weights: &[f64]) -> Vec<Vec<f64>> {
+        let q = self.unicrit_positive_flows.len();
         if weights.len() != q {
             panic!(
                 "Wrong number of weights given, {} given, {} expected",
@@ -114,540 +377,4680 @@ impl PrometheeProblem {
                 q
             );
         }
+        let tot_w = check_weight_sum(weights).unwrap_or_else(|e| panic!("{}", e));
 
-        let argsorted_eval_matrix = generalized_criteria
+        (0..self.positive_flows.len())
+            .map(|ai| {
+                (0..q)
+                    .map(|k| weights[k] / tot_w * self.unicriterion_net_flow(k, ai).unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Dispersion of the rows of `weighted_unicriterion_net_flows` for each alternative. The mean times q is
+    /// the net flow of the alternative when the weights are the ones used to compute this result, a high
+    /// standard deviation means that the alternative is much better on some criteria than on others.
+    /// See `SolvedProblem::flow_dispersion` to use the weights of the problem.
+    pub fn flow_dispersion_with_weights(&self, weights: &[f64]) -> Vec<FlowDispersion> {
+        self.weighted_unicriterion_net_flows(weights)
             .iter()
-            .enumerate()
-            .map(|(k, criterion)| match criterion {
-                GeneralizedCriterion::Linear { q: _, p: _ }
-                | GeneralizedCriterion::VShape { p: _ }
-                | GeneralizedCriterion::UShape { p: _ } => {
-                    let fks =
-                        |alt: usize| -> f64 { alt_table.performance(alt, k).unwrap().to_owned() };
-                    let mut argsorted_fks: Vec<usize> = (0..n).collect();
-                    argsorted_fks.sort_unstable_by(|&i, &j| fks(i).partial_cmp(&fks(j)).unwrap());
-                    Some(argsorted_fks)
+            .map(|contributions| {
+                let q = contributions.len() as f64;
+                let mean = contributions.iter().sum::<f64>() / q;
+                let variance = contributions
+                    .iter()
+                    .map(|f| (f - mean).powi(2))
+                    .sum::<f64>()
+                    / q;
+                FlowDispersion {
+                    min: contributions.iter().copied().fold(f64::INFINITY, f64::min),
+                    max: contributions
+                        .iter()
+                        .copied()
+                        .fold(f64::NEG_INFINITY, f64::max),
+                    mean,
+                    std: variance.sqrt(),
                 }
-                GeneralizedCriterion::Usual => None,
-                _ => unimplemented!("Not implemented for this criterion"),
             })
-            .collect();
-
-        Self {
-            n,
-            q,
-            alt_table,
-            generalized_criteria,
-            weights: weights.to_vec(),
-            argsorted_eval_matrix,
-        }
+            .collect()
     }
 
-    fn argsort_evals(&mut self, k: usize) {
-        self.argsorted_eval_matrix[k] = {
-            let fks =
-                |alt: usize| -> f64 { self.alt_table.performance(alt, k).unwrap().to_owned() };
-
-            let mut argsorted_fks: Vec<usize> = (0..self.n()).collect();
-            argsorted_fks.sort_unstable_by(|&i, &j| fks(i).partial_cmp(&fks(j)).unwrap());
-            Some(argsorted_fks)
-        };
+    /// Discrimination power of each criterion: the variance of its unicriterion net flows over the
+    /// alternatives. A criterion close to zero barely separates the alternatives.
+    pub fn criterion_discrimination(&self) -> Vec<f64> {
+        (0..self.unicrit_positive_flows.len())
+            .map(|k| {
+                let flows = self.unicriterion_net_flows(k);
+                let n = flows.len() as f64;
+                let mean = flows.iter().sum::<f64>() / n;
+                flows.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / n
+            })
+            .collect()
     }
 
-    pub fn n(&self) -> usize {
-        self.n
+    /// Net flow differences between consecutive alternatives of the ranking, from the top, of length n-1
+    pub fn rank_gaps(&self) -> Vec<f64> {
+        let net_flows = self.net_flows();
+        self.ranked_alts()
+            .windows(2)
+            .map(|w| net_flows[w[0]] - net_flows[w[1]])
+            .collect()
     }
 
-    pub fn q(&self) -> usize {
-        self.q
+    /// Net flow difference between the first and the second alternatives of the ranking,
+    /// 0 if there is a single alternative
+    pub fn winner_margin(&self) -> f64 {
+        self.rank_gaps().first().copied().unwrap_or(0.0)
     }
 
-    pub fn w(&self, k: usize) -> Option<&f64> {
-        self.weights.get(k)
-    }
+    /// Same as `ranked_alts`, but returns an error instead of panicking when a net flow is NaN or infinite
+    pub fn try_ranked_alts(&self) -> Result<Vec<usize>, RankingError> {
+        if let Some(ai) = self.net_flows().iter().position(|f| !f.is_finite()) {
+            let criterion = (0..self.unicrit_positive_flows.len()).find(|&k| {
+                !self.unicrit_positive_flows[k][ai].is_finite()
+                    || !self.unicrit_negative_flows[k][ai].is_finite()
+            });
+            return Err(RankingError::NonFiniteFlow {
+                alternative: ai,
+                criterion,
+            });
+        }
 
-    pub fn pref_fun(&self, k: usize) -> Option<&GeneralizedCriterion> {
-        self.generalized_criteria.get(k)
+        Ok(self.ranked_alts())
     }
 
-    pub fn alt_name(&self, i: usize) -> Option<&str> {
-        self.alt_table.alt_name(i)
+    /// Return the (n, q) matrix of the derivatives of the net flow of each alternative with respect to each weight.
+    /// Since the weights are normalized, the net flow is phi(i) = sum_k w_k phi_k(i) / sum_k w_k,
+    /// whose derivative with respect to w_k is (phi_k(i) - phi(i)) / sum_k w_k.
+    /// The derivatives are given at the normalized weights, for raw weights summing to W they must be divided by W.
+    pub fn net_flow_gradient(&self) -> Vec<Vec<f64>> {
+        let q = self.unicrit_positive_flows.len();
+        let unicrit_net_flows: Vec<Vec<f64>> =
+            (0..q).map(|k| self.unicriterion_net_flows(k)).collect();
+
+        self.net_flows()
+            .iter()
+            .enumerate()
+            .map(|(i, net_flow)| {
+                unicrit_net_flows
+                    .iter()
+                    .map(|flows_k| flows_k[i] - net_flow)
+                    .collect()
+            })
+            .collect()
     }
 
-    pub fn alt_names(&self) -> Vec<&str> {
-        self.alt_table.alt_names()
+    /// Return the alternatives ranked in descending order of net flow, where ties are broken by
+    /// a secondary key in descending order (for instance the positive flows or the evaluations on a criterion),
+    /// and then by ascending index.
+    /// Net flows are considered tied if they differ by at most `FLOW_EPS` from the previous alternative in the ranking.
+    pub fn ranked_alts_tiebreak(&self, secondary: &[f64]) -> Vec<usize> {
+        let net_flows = self.net_flows();
+        if secondary.len() != net_flows.len() {
+            panic!(
+                "Wrong number of secondary keys given, {} given, {} expected",
+                secondary.len(),
+                net_flows.len()
+            );
+        }
+
+        rank_with_ties(&net_flows, |&i, &j| {
+            secondary[j]
+                .partial_cmp(&secondary[i])
+                .unwrap()
+                .then(i.cmp(&j))
+        })
     }
 
-    pub fn criterion_name(&self, k: usize) -> Option<&str> {
-        self.alt_table.criterion_name(k)
+    /// Promethee III intervals `[phi - alpha * sigma, phi + alpha * sigma]` of each alternative,
+    /// where sigma is the standard deviation of its unicriterion net flows
+    pub fn promethee3_intervals(&self, alpha: f64) -> Vec<(f64, f64)> {
+        let q = self.unicrit_positive_flows.len();
+        self.net_flows()
+            .into_iter()
+            .enumerate()
+            .map(|(ai, net_flow)| {
+                let profile: Vec<f64> = (0..q)
+                    .map(|k| self.unicriterion_net_flow(k, ai).unwrap())
+                    .collect();
+                let mean = profile.iter().sum::<f64>() / q as f64;
+                let sigma =
+                    (profile.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / q as f64).sqrt();
+                (net_flow - alpha * sigma, net_flow + alpha * sigma)
+            })
+            .collect()
     }
 
-    pub fn criteria_names(&self) -> &[Box<str>] {
-        self.alt_table.criteria_names()
+    /// Promethee III partial order: pairs (a, b) such that the interval of a lies entirely above
+    /// the interval of b. Alternatives with overlapping intervals are indifferent.
+    pub fn promethee3_order(&self, alpha: f64) -> Vec<(usize, usize)> {
+        let intervals = self.promethee3_intervals(alpha);
+        intervals
+            .iter()
+            .enumerate()
+            .flat_map(|(a, (low_a, _))| {
+                intervals
+                    .iter()
+                    .enumerate()
+                    .filter(move |(_, (_, up_b))| low_a > up_b)
+                    .map(move |(b, _)| (a, b))
+            })
+            .collect()
     }
 
-    pub fn smallest_p_vshape(&self, k: usize) -> Option<f64> {
-        if k >= self.q {
-            None
-        } else {
-            match self.generalized_criteria[k] {
-                GeneralizedCriterion::VShape { p: _ } => Some(
-                    self.argsorted_eval_matrix[k]
-                        .as_ref()
-                        .expect("to be computed at init")
-                        .windows(2)
-                        .map(|w| self.perf(k, w[1]).unwrap() - self.perf(k, w[0]).unwrap())
-                        .fold(
-                            f64::INFINITY,
-                            |acc, b| {
-                                if b == 0.0 {
-                                    acc
-                                } else {
-                                    acc.min(b)
-                                }
-                            },
-                        ),
-                ),
-                _ => None,
-            }
+    /// Return the alternative preferred to every other one on the aggregated pairwise preference,
+    /// i.e. with `pi(a, b) > pi(b, a)` for all b, if it exists.
+    /// The winner may differ from the best net flow. This computes the preference matrix of the
+    /// problem with the pairwise O(n²q) method.
+    pub fn condorcet_winner(&self, problem: &PrometheeProblem) -> Option<usize> {
+        if problem.n() != self.positive_flows.len() {
+            panic!(
+                "Wrong number of alternatives in the problem, {} given, {} expected",
+                problem.n(),
+                self.positive_flows.len()
+            );
         }
+
+        let pi = problem.preference_matrix();
+        (0..pi.len()).find(|&a| (0..pi.len()).all(|b| a == b || pi[a][b] > pi[b][a]))
     }
 
-    pub fn fast_pos_unicriterion_flow(
-        &self,
-        k: usize,
-        q: f64,
-        p: f64,
-        argsorted_fks: &[usize],
-    ) -> Vec<f64> {
-        let fks = |alt: usize| -> f64 { self.alt_table.performance(alt, k).unwrap().to_owned() };
-        let mut positive_flow = vec![0.0; self.n];
-        let (mut w, mut r) = (
-            VecDeque::<usize>::new(),
-            VecDeque::from(argsorted_fks.to_owned()),
+    /// Return true if the net flow of a1 is greater than the one of a2 by more than `FLOW_EPS`
+    pub fn is_better(&self, a1: usize, a2: usize) -> bool {
+        let (phi_1, phi_2) = (
+            self.positive_flows[a1] - self.negative_flows[a1],
+            self.positive_flows[a2] - self.negative_flows[a2],
         );
-        let mut card_l = 0;
-        let mut sum = 0.0;
-        let (mut low, mut up): (f64, f64);
-        let (mut const_fact, mut last_term): (f64, f64);
+        phi_1 > phi_2 && !flows_equal(phi_1, phi_2)
+    }
+}
 
-        for &idx in argsorted_fks {
-            low = fks(idx) - p;
-            up = fks(idx) - q;
+/// Spread of the weighted unicriterion net flows of an alternative over the criteria
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FlowDispersion {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Population standard deviation
+    pub std: f64,
+}
 
-            // Remove elements leaving window
-            while !w.is_empty() && fks(*w.front().unwrap()) <= low {
-                sum -= fks(w.pop_front().unwrap());
-                card_l += 1;
-            }
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankingEntry {
+    /// Index of the alternative in the problem
+    pub index: usize,
+    pub name: String,
+    pub net_flow: f64,
+}
 
-            // Remove elements leaving the right part
-            while !r.is_empty() && fks(*r.front().unwrap()) <= up {
-                let x = r.pop_front().unwrap();
-                if fks(x) >= low {
-                    w.push_back(x);
-                    sum += fks(x);
-                } else {
-                    card_l += 1;
-                }
-            }
+/// Alternatives ranked in descending order of net flow, along with their names
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ranking {
+    pub entries: Vec<RankingEntry>,
+}
 
-            (const_fact, last_term) = match p != q {
-                true => ((fks(idx) - q) / (p - q), -sum / (p - q)),
-                false => (0.0, 0.0),
-            };
-            positive_flow[idx] = 1.0 / (self.n as f64 - 1.0)
-                * (card_l as f64 + w.len() as f64 * const_fact + last_term);
-        }
-        positive_flow
+impl Ranking {
+    pub fn len(&self) -> usize {
+        self.entries.len()
     }
 
-    pub fn perf(&self, k: usize, i: usize) -> Option<&f64> {
-        self.alt_table.performance(i, k)
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
     }
 
-    pub fn fast_neg_unicriterion_flow(
-        &self,
-        k: usize,
-        q: f64,
-        p: f64,
-        argsorted_fks: &[usize],
-    ) -> Vec<f64> {
-        let fks = |alt: usize| -> f64 { self.alt_table.performance(alt, k).unwrap().to_owned() };
-        let mut negative_flows = vec![0.0; self.n];
-        let (mut l, mut w) = (
-            VecDeque::from(argsorted_fks.to_owned()),
-            VecDeque::<usize>::new(),
-        );
-        let mut card_r = 0;
-        let mut sum = 0.0;
-        let (mut low, mut up): (f64, f64);
-        let (mut const_fact, mut last_term): (f64, f64);
+    /// Entry at the given position, starting from 0 for the best alternative
+    pub fn position(&self, pos: usize) -> Option<&RankingEntry> {
+        self.entries.get(pos)
+    }
 
-        for &idx in argsorted_fks.iter().rev() {
-            low = fks(idx) + q;
-            up = fks(idx) + p;
+    pub fn indices(&self) -> Vec<usize> {
+        self.entries.iter().map(|entry| entry.index).collect()
+    }
+}
 
-            // Remove elements leaving window
-            while !w.is_empty() && fks(*w.back().expect("not empty")) >= up {
-                sum -= fks(w.pop_back().expect("not empty"));
-                card_r += 1;
-            }
+/// Row of a full results table, see `PrometheeProblem::full_ranking`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankRow {
+    /// Rank starting at 1, tied alternatives share the same rank and the next ones skip it (1, 2, 2, 4)
+    pub rank: usize,
+    /// Index of the alternative in the problem
+    pub index: usize,
+    pub name: String,
+    pub net_flow: f64,
+    pub positive_flow: f64,
+    pub negative_flow: f64,
+}
 
-            // Remove elements leaving the left part
-            while !l.is_empty() && fks(*l.back().expect("not empty")) >= low {
-                let x = l.pop_back().expect("not empty");
-                if fks(x) <= up {
-                    w.push_front(x);
-                    sum += fks(x);
-                } else {
-                    card_r += 1;
-                }
-            }
+/// Positive and negative flows of each criterion taken alone, independent of the weights
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnicriterionFlows {
+    pub unicrit_positive_flows: Vec<Vec<f64>>,
+    pub unicrit_negative_flows: Vec<Vec<f64>>,
+}
 
-            if p != q {
-                const_fact = (fks(idx) + q) / (p - q);
-                last_term = sum / (p - q);
-            } else {
-                (const_fact, last_term) = (0.0, 0.0);
+impl UnicriterionFlows {
+    pub fn q(&self) -> usize {
+        self.unicrit_positive_flows.len()
+    }
+
+    /// Aggregate the unicriterion flows with the given weights, normalized so that they sum to one.
+    /// This only performs the weighted sum, in O(qn).
+    pub fn aggregate(&self, weights: &[f64]) -> Promethee2Result {
+        if weights.len() != self.q() {
+            panic!(
+                "Wrong number of weights given, {} given, {} expected",
+                weights.len(),
+                self.q()
+            );
+        }
+
+        let tot_w = check_weight_sum(weights).unwrap_or_else(|e| panic!("{}", e));
+        let normalized_weights: Vec<f64> = weights.iter().map(|w| w / tot_w).collect();
+        let (positive_flows, negative_flows) = self.weighted_sum(&normalized_weights);
+
+        Promethee2Result {
+            positive_flows,
+            unicrit_positive_flows: self.unicrit_positive_flows.clone(),
+            negative_flows,
+            unicrit_negative_flows: self.unicrit_negative_flows.clone(),
+        }
+    }
+
+    fn weighted_sum(&self, weights: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        let n = self.unicrit_positive_flows.first().map_or(0, |f| f.len());
+        let mut positive_flows: Vec<f64> = vec![0.0; n];
+        let mut negative_flows: Vec<f64> = vec![0.0; n];
+
+        for (k, w) in weights.iter().enumerate() {
+            for i in 0..n {
+                positive_flows[i] += w * self.unicrit_positive_flows[k][i];
+                negative_flows[i] += w * self.unicrit_negative_flows[k][i];
             }
-            negative_flows[idx] = 1.0 / (self.n as f64 - 1.0)
-                * (card_r as f64 - (w.len() as f64 * const_fact) + last_term);
         }
-        negative_flows
+        (positive_flows, negative_flows)
     }
+}
 
-    /// Compute the unicriterion positive and negative flows for criterion k
-    /// using the O(qnlogn) method from Van Asche, 2018
-    fn fast_unicriterion_flows(&self, k: usize) -> Option<(Vec<f64>, Vec<f64>)> {
-        if k >= self.q {
-            return None;
+/// Relation of an alternative of a ranking with the previous one, see `Promethee2Result::ranked_with_relations`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RankRelation {
+    /// First alternative of the ranking
+    First,
+    /// Strictly worse than the previous alternative, starting a new group of tied alternatives
+    WorseThanPrevious,
+    /// Tied with the previous alternative
+    TiedWithPrevious,
+}
+
+/// Relation between two alternatives a and b in the Promethee I partial preorder.
+/// It is serialized compactly as an integer: 0 Prefers, 1 Indifferent, 2 Incomparable, 3 PreferredBy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum PartialPreference {
+    /// a is preferred to b
+    Prefers,
+    /// b is preferred to a
+    PreferredBy,
+    Indifferent,
+    Incomparable,
+}
+
+impl From<PartialPreference> for u8 {
+    fn from(preference: PartialPreference) -> Self {
+        match preference {
+            PartialPreference::Prefers => 0,
+            PartialPreference::Indifferent => 1,
+            PartialPreference::Incomparable => 2,
+            PartialPreference::PreferredBy => 3,
         }
-        let (q, p) = match self.generalized_criteria[k] {
-            GeneralizedCriterion::VShape { p } => (0.0, p),
-            GeneralizedCriterion::Linear { q, p } => (q, p),
-            _ => panic!("Wrong type of criterion for fast method"),
-        };
-        //
-        // We work with argsort instead of sort to work with usize instead of ints
-        let argsorted_fks = self.argsorted_eval_matrix[k]
-            .as_ref()
-            .expect("to be computed at construction");
+    }
+}
 
-        let positive_flow = self.fast_pos_unicriterion_flow(k, q, p, argsorted_fks);
-        let negative_flows = self.fast_neg_unicriterion_flow(k, q, p, argsorted_fks);
-        Some((positive_flow, negative_flows))
+impl TryFrom<u8> for PartialPreference {
+    type Error = String;
+
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        match code {
+            0 => Ok(PartialPreference::Prefers),
+            1 => Ok(PartialPreference::Indifferent),
+            2 => Ok(PartialPreference::Incomparable),
+            3 => Ok(PartialPreference::PreferredBy),
+            _ => Err(format!("Invalid partial preference code {}", code)),
+        }
     }
+}
 
-    fn slow_unicriterion_flows(
-        &self,
-        dist_mat: &[Vec<f64>],
-        generalized_criterion: &GeneralizedCriterion,
-    ) -> (Vec<f64>, Vec<f64>) {
-        dist_mat
-            .iter()
-            .map(|di| {
-                let (pos, neg): (f64, f64) = (*di)
-                    .iter()
-                    .map(|&dij| {
-                        (
-                            generalized_criterion.normalisation(dij),
-                            generalized_criterion.normalisation(-dij),
-                        )
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Promethee1Result {
+    pub positive_flows: Vec<f64>,
+    pub negative_flows: Vec<f64>,
+    /// Matrix of size (n, n), entry (a, b) is the relation of a with respect to b
+    preferences: Vec<Vec<PartialPreference>>,
+}
+
+impl Promethee1Result {
+    /// Build the partial preorder from the positive and negative flows.
+    /// a is preferred to b if it is at least as good on both flows and strictly better on one,
+    /// indifferent if both flows are equal, and incomparable otherwise.
+    pub fn new(positive_flows: Vec<f64>, negative_flows: Vec<f64>) -> Self {
+        Self::with_eps(positive_flows, negative_flows, 0.0)
+    }
+
+    /// Same as `new`, with flows differing by at most eps considered equal,
+    /// so that negligible differences give indifferences rather than strict preferences
+    pub fn with_eps(positive_flows: Vec<f64>, negative_flows: Vec<f64>, eps: f64) -> Self {
+        if positive_flows.len() != negative_flows.len() {
+            panic!(
+                "Inconsistent flows, {} positive and {} negative flows given",
+                positive_flows.len(),
+                negative_flows.len()
+            );
+        }
+
+        let n = positive_flows.len();
+        let preferences = (0..n)
+            .map(|a| {
+                (0..n)
+                    .map(|b| {
+                        let cmp = |x: f64, y: f64| {
+                            if (x - y).abs() <= eps {
+                                Some(std::cmp::Ordering::Equal)
+                            } else {
+                                x.partial_cmp(&y)
+                            }
+                        };
+                        let pos = cmp(positive_flows[a], positive_flows[b]);
+                        // A lower negative flow is better
+                        let neg = cmp(negative_flows[b], negative_flows[a]);
+                        use std::cmp::Ordering::*;
+                        match (pos, neg) {
+                            (Some(Equal), Some(Equal)) => PartialPreference::Indifferent,
+                            (Some(Greater), Some(Greater | Equal))
+                            | (Some(Equal), Some(Greater)) => PartialPreference::Prefers,
+                            (Some(Less), Some(Less | Equal)) | (Some(Equal), Some(Less)) => {
+                                PartialPreference::PreferredBy
+                            }
+                            _ => PartialPreference::Incomparable,
+                        }
                     })
-                    .fold((0.0, 0.0), |(acc_p, acc_neg), (pos, neg)| {
-                        (acc_p + pos, acc_neg + neg)
-                    });
-                (pos / (self.n as f64 - 1.0), neg / (self.n as f64 - 1.0))
+                    .collect()
             })
-            .unzip()
-    }
+            .collect();
 
-    fn unicriterion_flows(&self, k: usize) -> Option<(Vec<f64>, Vec<f64>)> {
-        if k >= self.q {
-            panic!("Wrong criterion index used, {}>{}", k, self.q)
+        Self {
+            positive_flows,
+            negative_flows,
+            preferences,
         }
+    }
 
-        let generalized_criterion = &self.generalized_criteria[k];
+    pub fn n(&self) -> usize {
+        self.positive_flows.len()
+    }
 
-        match generalized_criterion {
-            GeneralizedCriterion::VShape { p: _ } | GeneralizedCriterion::Linear { q: _, p: _ } => {
-                self.fast_unicriterion_flows(k)
+    /// Relation of alternative a with respect to alternative b
+    pub fn relation(&self, a: usize, b: usize) -> Option<PartialPreference> {
+        self.preferences.get(a)?.get(b).copied()
+    }
+
+    pub fn preferences(&self) -> &[Vec<PartialPreference>] {
+        &self.preferences
+    }
+
+    /// Return the pairs (a, b), with a < b, of incomparable alternatives
+    pub fn incomparable_pairs(&self) -> Vec<(usize, usize)> {
+        (0..self.n())
+            .tuple_combinations()
+            .filter(|&(a, b)| self.preferences[a][b] == PartialPreference::Incomparable)
+            .collect()
+    }
+
+    /// Number of unordered pairs of incomparable alternatives
+    pub fn incomparability_count(&self) -> usize {
+        self.incomparable_pairs().len()
+    }
+
+    /// For each alternative, the number of other alternatives it is strictly preferred to
+    pub fn outrank_counts(&self) -> Vec<usize> {
+        self.preferences
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .filter(|&&relation| relation == PartialPreference::Prefers)
+                    .count()
+            })
+            .collect()
+    }
+}
+
+/// Number of comparisons the preference degrees of an alternative are divided by in the flows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlowNormalization {
+    /// n-1, the alternative is not compared to itself
+    #[default]
+    NMinusOne,
+    /// n, as if the alternative was also compared to itself with a zero preference
+    N,
+}
+
+/// A NaN evaluation marks a missing value, accepted only by a builder with `allow_missing`: the alternative
+/// is indifferent to all the others on this criterion, and its pairs are left out of the flows of the criterion, which are normalized by the
+/// number of comparisons actually made.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(from = "PrometheeProblemData")]
+pub struct PrometheeProblem {
+    n: usize,
+    q: usize,
+    /// Matrix of criteria evaluations of size (q, n)
+    alt_table: AlternativeTable,
+    /// For each criterion, the Option may contain the indices of the alternatives sorted in ascending order of evaluations.
+    /// This cache is not serialized, it is rebuilt from the evaluations on deserialization.
+    #[serde(skip)]
+    argsorted_eval_matrix: Vec<Option<Vec<usize>>>,
+    generalized_criteria: Vec<GeneralizedCriterion>,
+    weights: Vec<f64>,
+    /// If true, the evaluations of Min criteria are kept as is and the preference direction
+    /// is flipped inside the flow computations instead
+    #[serde(default)]
+    preserve_signs: bool,
+    #[serde(default)]
+    flow_normalization: FlowNormalization,
+    /// Direction of each criterion according to the source of the problem, e.g. a criterion declared as
+    /// a cost in a config, None if not declared. Empty if nothing was declared at all.
+    #[serde(default)]
+    declared_directions: Vec<Option<OptimizationDirection>>,
+}
+
+/// Serialized form of a `PrometheeProblem`, everything but the argsort cache
+#[derive(Deserialize)]
+struct PrometheeProblemData {
+    n: usize,
+    q: usize,
+    alt_table: AlternativeTable,
+    generalized_criteria: Vec<GeneralizedCriterion>,
+    weights: Vec<f64>,
+    #[serde(default)]
+    preserve_signs: bool,
+    #[serde(default)]
+    flow_normalization: FlowNormalization,
+    #[serde(default)]
+    declared_directions: Vec<Option<OptimizationDirection>>,
+}
+
+impl From<PrometheeProblemData> for PrometheeProblem {
+    fn from(data: PrometheeProblemData) -> Self {
+        let mut problem = Self {
+            n: data.n,
+            q: data.q,
+            alt_table: data.alt_table,
+            argsorted_eval_matrix: vec![None; data.q],
+            generalized_criteria: data.generalized_criteria,
+            weights: data.weights,
+            preserve_signs: data.preserve_signs,
+            flow_normalization: data.flow_normalization,
+            declared_directions: data.declared_directions,
+        };
+        problem.rebuild_cache();
+        problem
+    }
+}
+
+impl PrometheeProblem {
+    /// Build a problem, panicking on invalid inputs: see `try_new` for the checks made
+    pub fn new(
+        alt_table: AlternativeTable,
+        generalized_criteria: Vec<GeneralizedCriterion>,
+        weights: Vec<f64>,
+    ) -> Self {
+        Self::try_build(alt_table, generalized_criteria, weights, false, false)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as `new`, but returns an error instead of panicking on invalid inputs: sizes that do not match
+    /// the table, fewer than two alternatives, non-finite (including missing) evaluations, invalid weights
+    /// or thresholds
+    pub fn try_new(
+        alt_table: AlternativeTable,
+        generalized_criteria: Vec<GeneralizedCriterion>,
+        weights: Vec<f64>,
+    ) -> Result<Self, PrometheeError> {
+        Self::try_build(alt_table, generalized_criteria, weights, false, false)
+    }
+
+    /// Shared checks of `try_new` and the builder, NaN evaluations are accepted as missing values
+    /// only if `allow_missing`
+    fn try_build(
+        alt_table: AlternativeTable,
+        generalized_criteria: Vec<GeneralizedCriterion>,
+        weights: Vec<f64>,
+        preserve_signs: bool,
+        allow_missing: bool,
+    ) -> Result<Self, PrometheeError> {
+        let q = alt_table.q();
+        for (field, given) in [
+            ("generalized criteria", generalized_criteria.len()),
+            ("weights", weights.len()),
+        ] {
+            if given != q {
+                return Err(PrometheeError::DimensionMismatch {
+                    field: field.to_string(),
+                    given,
+                    expected: q,
+                });
             }
-            _ => {
-                let dist_mat: Vec<Vec<f64>> = self
-                    .alt_table
-                    .criterion(k)
-                    .unwrap()
-                    .iter()
-                    .map(|&a_i| {
-                        self.alt_table
-                            .criterion(k)
-                            .unwrap()
-                            .iter()
-                            .map(move |&a_j| a_i - a_j.to_owned())
-                            .collect()
-                    })
-                    .collect();
-                Some(self.slow_unicriterion_flows(&dist_mat, generalized_criterion))
+        }
+        if alt_table.n() < MIN_ALTERNATIVES {
+            return Err(PrometheeError::TooFewAlternatives {
+                given: alt_table.n(),
+                min: MIN_ALTERNATIVES,
+            });
+        }
+        for alt in alt_table.alternatives() {
+            if let Some(criterion) = alt
+                .perfs()
+                .iter()
+                .position(|perf| !(perf.is_finite() || allow_missing && perf.is_nan()))
+            {
+                return Err(PrometheeError::NonFiniteEvaluation {
+                    alt: alt.name().to_string(),
+                    criterion,
+                });
             }
         }
+        check_weight_sum(&weights)?;
+        check_criteria(&generalized_criteria)?;
+
+        Ok(Self::build(
+            alt_table,
+            generalized_criteria,
+            weights,
+            preserve_signs,
+        ))
     }
 
-    pub fn solve(&self) -> Promethee2Result {
-        let mut positive_flows: Vec<f64> = vec![0.0; self.n];
-        let mut negative_flows: Vec<f64> = vec![0.0; self.n];
+    /// Build a problem whose Min criteria keep their true evaluations: smaller values are preferred
+    /// for these criteria when computing the flows, and `perf` returns the values as given.
+    pub fn new_preserve_signs(
+        alt_table: AlternativeTable,
+        generalized_criteria: Vec<GeneralizedCriterion>,
+        weights: Vec<f64>,
+    ) -> Self {
+        Self::try_build(alt_table, generalized_criteria, weights, true, false)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        let mut pos_unicriterion_flow: Vec<f64>;
-        let mut positive_unicriterions_flows: Vec<Vec<f64>> = Vec::new();
-        let mut neg_unicriterion_flow: Vec<f64>;
-        let mut negative_unicriterions_flows: Vec<Vec<f64>> = Vec::new();
+    fn build(
+        alt_table: AlternativeTable,
+        generalized_criteria: Vec<GeneralizedCriterion>,
+        mut weights: Vec<f64>,
+        preserve_signs: bool,
+    ) -> Self {
+        if let Err(e) = check_weight_sum(&weights).and(check_criteria(&generalized_criteria)) {
+            panic!("{}", e);
+        }
 
-        for k in 0..self.q {
-            // compute positive and negative unicriterion flow and add it to the global
-            (pos_unicriterion_flow, neg_unicriterion_flow) = self.unicriterion_flows(k).unwrap();
-            positive_unicriterions_flows.push(pos_unicriterion_flow);
-            negative_unicriterions_flows.push(neg_unicriterion_flow);
+        // normalize weights
+        let tot_w: f64 = weights.iter().sum();
+        weights = weights.into_iter().map(|w| w / tot_w).collect();
 
-            for i in 0..self.n {
-                positive_flows[i] +=
-                    self.weights[k] * positive_unicriterions_flows.last().unwrap()[i];
-                negative_flows[i] +=
-                    self.weights[k] * negative_unicriterions_flows.last().unwrap()[i];
-            }
+        // TODO todo!("Use optimization direction somehow");
+
+        let n = alt_table.n();
+        let q = alt_table.q();
+
+        // Verify validity of inputs
+        if generalized_criteria.len() != q {
+            panic!(
+                "Wrong number of generalized criteria given, {} given, {} expected",
+                generalized_criteria.len(),
+                q
+            );
         }
 
-        Promethee2Result {
-            positive_flows,
-            unicrit_positive_flows: positive_unicriterions_flows,
-            negative_flows,
-            unicrit_negative_flows: negative_unicriterions_flows,
+        if weights.len() != q {
+            panic!(
+                "Wrong number of weights given, {} given, {} expected",
+                weights.len(),
+                q
+            );
         }
+
+        let mut problem = Self {
+            n,
+            q,
+            alt_table,
+            generalized_criteria,
+            weights: weights.to_vec(),
+            argsorted_eval_matrix: vec![None; q],
+            preserve_signs,
+            flow_normalization: FlowNormalization::default(),
+            declared_directions: Vec::new(),
+        };
+
+        problem.rebuild_cache();
+        problem
     }
 
-    pub fn get_parameter(&self, k: usize) -> f64 {
-        match self.generalized_criteria[k] {
-            crate::generalized_criterion::GeneralizedCriterion::VShape { p } => p,
-            _ => panic!("Invalid criterion"),
+    /// Compute objective weights with the entropy method: criteria whose evaluations are more
+    /// dispersed among the alternatives get higher weights. Each column is first rescaled to [0, 1],
+    /// constant criteria get a zero weight. The returned weights sum to one.
+    pub fn entropy_weights(table: &AlternativeTable) -> Vec<f64> {
+        let n = table.n();
+        let q = table.q();
+        if n < 2 {
+            return vec![1.0 / q as f64; q];
+        }
+
+        let divergences: Vec<f64> = table
+            .criteria()
+            .into_iter()
+            .map(|column| {
+                let (min, max) = column
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &f| {
+                        (min.min(f), max.max(f))
+                    });
+                let tot: f64 = column.iter().map(|f| f - min).sum();
+                if max - min <= 0.0 || tot <= 0.0 {
+                    return 0.0;
+                }
+
+                let entropy: f64 = -column
+                    .iter()
+                    .map(|f| (f - min) / tot)
+                    .filter(|&p| p > 0.0)
+                    .map(|p| p * p.ln())
+                    .sum::<f64>()
+                    / (n as f64).ln();
+                1.0 - entropy
+            })
+            .collect();
+
+        let tot_divergence: f64 = divergences.iter().sum();
+        if tot_divergence <= 0.0 {
+            vec![1.0 / q as f64; q]
+        } else {
+            divergences.iter().map(|d| d / tot_divergence).collect()
         }
     }
 
-    /// Return the evaluation of the alternatives for criterion k, sorted in ascending order
-    /// If the evaluation matrix is not sorted, compute it
-    pub fn sorted_evals(&self, k: usize) -> Vec<f64> {
-        match self.argsorted_eval_matrix[k].as_ref() {
-            Some(sorted_indices) => sorted_indices
-                .iter()
-                .map(|&i| self.perf(k, i).unwrap().to_owned())
-                .collect(),
-            None => {
-                let mut sorted_fks = self.alt_table.criterion(k).unwrap();
-                sorted_fks.sort_unstable_by(|&i, &j| i.partial_cmp(&j).unwrap());
-                sorted_fks
-            }
-        }
+    /// Suggest a generalized criterion for each criterion of the table, from its distribution of values.
+    /// Columns with at most two distinct values get a Usual criterion, others a VShape with p set
+    /// to the interquartile range (or the full range if the interquartile range is zero).
+    /// Missing (NaN) evaluations are ignored.
+    /// These heuristics are only advisory, the suggestions are meant to be reviewed and edited.
+    pub fn suggest_criteria(table: &AlternativeTable) -> Vec<GeneralizedCriterion> {
+        table
+            .criteria()
+            .into_iter()
+            .map(|mut column| {
+                column.retain(|f| !f.is_nan());
+                column.sort_unstable_by(|a, b| a.total_cmp(b));
+                let n_distinct = column.iter().dedup().count();
+
+                if n_distinct <= 2 {
+                    GeneralizedCriterion::Usual
+                } else {
+                    GeneralizedCriterion::VShape {
+                        p: Self::auto_threshold(&column),
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Same problem with the evaluations normalized to [0, 1] (see `AlternativeTable::normalized`)
+    /// and the thresholds of each criterion rescaled accordingly, so that the flows are unchanged
+    pub fn normalized(&self) -> PrometheeProblem {
+        let generalized_criteria = (0..self.q)
+            .map(|k| {
+                let (min, max) = self.alt_table.criterion_bounds(k).unwrap();
+                if max > min {
+                    self.generalized_criteria[k].rescale(max - min, 1.0)
+                } else {
+                    self.generalized_criteria[k].clone()
+                }
+            })
+            .collect();
+
+        let mut problem = Self::build(
+            self.alt_table.normalized(),
+            generalized_criteria,
+            self.weights.clone(),
+            self.preserve_signs,
+        );
+        problem.flow_normalization = self.flow_normalization;
+        problem.declared_directions = self.declared_directions.clone();
+        problem
+    }
+
+    /// Indices of the criteria on which all the alternatives have the same value. Their flows are all
+    /// zero, so they only dilute the weights of the other criteria.
+    pub fn warn_flat_criteria(&self) -> Vec<usize> {
+        self.alt_table.flat_criteria(0.0)
+    }
+
+    /// Automatic preference threshold for a criterion: the interquartile range of its evaluations,
+    /// or their range if the interquartile range is zero. Missing (NaN) evaluations are ignored.
+    pub fn auto_threshold(values: &[f64]) -> f64 {
+        let mut sorted: Vec<f64> = values.iter().copied().filter(|f| !f.is_nan()).collect();
+        if sorted.is_empty() {
+            return 0.0;
+        }
+        sorted.sort_unstable_by(|a, b| a.total_cmp(b));
+
+        let iqr = quantile(&sorted, 0.75) - quantile(&sorted, 0.25);
+        if iqr > 0.0 {
+            iqr
+        } else {
+            sorted[sorted.len() - 1] - sorted[0]
+        }
+    }
+
+    /// Use the given normalization of the flows instead of the default n-1
+    pub fn with_flow_normalization(mut self, flow_normalization: FlowNormalization) -> Self {
+        self.flow_normalization = flow_normalization;
+        self
+    }
+
+    /// Record the direction each criterion is meant to have according to the source of the problem,
+    /// None for the criteria without such information. See `check_direction_consistency`.
+    pub fn with_declared_directions(
+        mut self,
+        declared_directions: Vec<Option<OptimizationDirection>>,
+    ) -> Self {
+        if declared_directions.len() != self.q {
+            panic!(
+                "Wrong number of declared directions given, {} given, {} expected",
+                declared_directions.len(),
+                self.q
+            );
+        }
+        self.declared_directions = declared_directions;
+        self
+    }
+
+    /// Check that the direction of each criterion in the table agrees with the declared one, if any,
+    /// and return the indices of the criteria where they disagree otherwise
+    pub fn check_direction_consistency(&self) -> Result<(), Vec<usize>> {
+        let conflicts: Vec<usize> = self
+            .declared_directions
+            .iter()
+            .enumerate()
+            .filter(|(k, declared)| {
+                declared
+                    .as_ref()
+                    .is_some_and(|direction| direction != self.alt_table.criterion_direction(*k))
+            })
+            .map(|(k, _)| k)
+            .collect();
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    /// Divisor of the sums of preference degrees in the flows
+    fn flow_norm(&self) -> f64 {
+        self.flow_norm_among(self.n)
+    }
+
+    /// Divisor of the sums of preference degrees in the flows computed among n alternatives only
+    fn flow_norm_among(&self, n: usize) -> f64 {
+        match self.flow_normalization {
+            FlowNormalization::NMinusOne => n as f64 - 1.0,
+            FlowNormalization::N => n as f64,
+        }
+    }
+
+    /// Sign to apply to the evaluations of criterion k so that it is maximized in the flow computations
+    fn criterion_sign(&self, k: usize) -> f64 {
+        // A corrupt deserialized problem may lack the direction, `validate` reports it
+        match (
+            self.preserve_signs,
+            self.alt_table.criteria_directions().get(k),
+        ) {
+            (true, Some(OptimizationDirection::Min)) => -1.0,
+            _ => 1.0,
+        }
+    }
+
+    /// Evaluations of the alternatives for criterion k, oriented so that higher is better
+    fn oriented_criterion(&self, k: usize) -> Vec<f64> {
+        let sign = self.criterion_sign(k);
+        self.alt_table
+            .criterion(k)
+            .unwrap()
+            .into_iter()
+            .map(|f| sign * f)
+            .collect()
+    }
+
+    fn argsort_evals(&mut self, k: usize) {
+        self.argsorted_eval_matrix[k] = {
+            let sign = self.criterion_sign(k);
+            let fks = |alt: usize| -> f64 { sign * self.alt_table.performance(alt, k).unwrap() };
+
+            let mut argsorted_fks: Vec<usize> = (0..self.n()).collect();
+            argsorted_fks.sort_unstable_by(|&i, &j| fks(i).partial_cmp(&fks(j)).unwrap());
+            Some(argsorted_fks)
+        };
+    }
+
+    /// Recompute the argsort cache of the criteria that use it from the current evaluations.
+    /// Criteria whose evaluations are missing, as in a corrupt deserialized problem or with NaN
+    /// evaluations, are left without cache so that `validate` reports the problem instead of panicking here.
+    pub fn rebuild_cache(&mut self) {
+        for k in 0..self.q {
+            self.rebuild_criterion_cache(k);
+        }
+    }
+
+    fn rebuild_criterion_cache(&mut self, k: usize) {
+        self.argsorted_eval_matrix[k] = None;
+        let evaluated = self.alt_table.alternatives().len() == self.n
+            && self
+                .alt_table
+                .alternatives()
+                .iter()
+                .all(|alt| alt.perf(k).is_some_and(|f| !f.is_nan()));
+        match self.generalized_criteria.get(k) {
+            Some(
+                GeneralizedCriterion::Linear { q: _, p: _ }
+                | GeneralizedCriterion::VShape { p: _ }
+                | GeneralizedCriterion::UShape { p: _ },
+            ) if evaluated => self.argsort_evals(k),
+            _ => (),
+        }
+    }
+
+    /// Check that the argsort cache of criterion k, if any, is a permutation of the alternatives
+    /// sorted in ascending order of evaluations. Criteria using the fast method must have a cache.
+    fn argsort_consistent(&self, k: usize) -> bool {
+        match &self.argsorted_eval_matrix[k] {
+            Some(argsorted_fks) => {
+                let sign = self.criterion_sign(k);
+                let mut seen = vec![false; self.n];
+                argsorted_fks.len() == self.n
+                    && argsorted_fks
+                        .iter()
+                        .all(|&i| i < self.n && !std::mem::replace(&mut seen[i], true))
+                    && argsorted_fks.windows(2).all(|w| {
+                        sign * self.perf(k, w[0]).unwrap() <= sign * self.perf(k, w[1]).unwrap()
+                    })
+            }
+            None => {
+                !matches!(
+                    self.generalized_criteria[k],
+                    GeneralizedCriterion::VShape { p: _ }
+                        | GeneralizedCriterion::Linear { q: _, p: _ }
+                ) || self.has_missing(k)
+            }
+        }
+    }
+
+    /// True if some alternative has a missing (NaN) evaluation on criterion k
+    fn has_missing(&self, k: usize) -> bool {
+        self.alt_table
+            .alternatives()
+            .iter()
+            .any(|alt| alt.perf(k).is_some_and(|f| f.is_nan()))
+    }
+
+    /// Verify that the argsort cache of every criterion matches the current evaluations
+    #[cfg(any(test, debug_assertions))]
+    pub fn debug_verify_cache(&self) -> bool {
+        self.argsorted_eval_matrix.len() == self.q
+            && (0..self.q).all(|k| self.argsort_consistent(k))
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn q(&self) -> usize {
+        self.q
+    }
+
+    pub fn w(&self, k: usize) -> Option<&f64> {
+        self.weights.get(k)
+    }
+
+    pub fn pref_fun(&self, k: usize) -> Option<&GeneralizedCriterion> {
+        self.generalized_criteria.get(k)
+    }
+
+    pub fn alt_name(&self, i: usize) -> Option<&str> {
+        self.alt_table.alt_name(i)
+    }
+
+    pub fn alt_names(&self) -> Vec<&str> {
+        self.alt_table.alt_names()
+    }
+
+    pub fn criterion_name(&self, k: usize) -> Option<&str> {
+        self.alt_table.criterion_name(k)
+    }
+
+    /// Hash of the content of the problem, stable across runs, to use as a cache key: the names and
+    /// evaluations of the alternatives, the names and directions of the criteria, the normalized weights,
+    /// the preference functions and the flow settings. The argsort cache is not part of the content.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        hasher.write_u64(self.n as u64);
+        hasher.write_u64(self.q as u64);
+        for alt in self.alt_table.alternatives() {
+            hasher.write_str(alt.name());
+            alt.perfs().iter().for_each(|&f| hasher.write_f64(f));
+        }
+        for k in 0..self.q {
+            hasher.write_str(self.criterion_name(k).unwrap());
+            hasher.write_u64(match self.alt_table.criterion_direction(k) {
+                OptimizationDirection::Min => 0,
+                OptimizationDirection::Max => 1,
+            });
+            hasher.write_f64(self.weights[k]);
+            let (tag, params) = match self.generalized_criteria[k] {
+                GeneralizedCriterion::UShape { p } => (0, vec![p]),
+                GeneralizedCriterion::VShape { p } => (1, vec![p]),
+                GeneralizedCriterion::Linear { q, p } => (2, vec![q, p]),
+                GeneralizedCriterion::Usual => (3, vec![]),
+                GeneralizedCriterion::AsymmetricLinear {
+                    q_pos,
+                    p_pos,
+                    q_neg,
+                    p_neg,
+                } => (4, vec![q_pos, p_pos, q_neg, p_neg]),
+            };
+            hasher.write_u64(tag);
+            params.into_iter().for_each(|param| hasher.write_f64(param));
+        }
+        hasher.write_u64(self.preserve_signs as u64);
+        hasher.write_u64(match self.flow_normalization {
+            FlowNormalization::NMinusOne => 0,
+            FlowNormalization::N => 1,
+        });
+        hasher.0
+    }
+
+    /// Return the triplets (criterion index, name, normalized weight) in descending order of weight,
+    /// criteria with the same weight being kept in index order
+    pub fn weights_ranked(&self) -> Vec<(usize, &str, f64)> {
+        (0..self.q)
+            .map(|k| (k, self.criterion_name(k).unwrap(), self.weights[k]))
+            .sorted_by(|(_, _, w_k), (_, _, w_l)| w_l.partial_cmp(w_k).unwrap())
+            .collect()
+    }
+
+    pub fn criteria_names(&self) -> &[Box<str>] {
+        self.alt_table.criteria_names()
+    }
+
+    pub fn smallest_p_vshape(&self, k: usize) -> Option<f64> {
+        if k >= self.q {
+            None
+        } else {
+            match self.generalized_criteria[k] {
+                GeneralizedCriterion::VShape { p: _ } => Some(
+                    self.sorted_evals(k)
+                        .into_iter()
+                        .filter(|f| !f.is_nan())
+                        .tuple_windows()
+                        .map(|(f0, f1)| f1 - f0)
+                        .fold(
+                            f64::INFINITY,
+                            |acc, b| {
+                                if b == 0.0 {
+                                    acc
+                                } else {
+                                    acc.min(b)
+                                }
+                            },
+                        ),
+                ),
+                _ => None,
+            }
+        }
+    }
+
+    pub fn fast_pos_unicriterion_flow(
+        &self,
+        k: usize,
+        q: f64,
+        p: f64,
+        argsorted_fks: &[usize],
+    ) -> Vec<f64> {
+        let sign = self.criterion_sign(k);
+        // The flows only depend on differences of evaluations, so they are shifted by the smallest one.
+        // This keeps the running sum small and avoids losing precision with large evaluations.
+        let offset = argsorted_fks.first().map_or(0.0, |&alt| {
+            sign * self.alt_table.performance(alt, k).unwrap()
+        });
+        let fks =
+            |alt: usize| -> f64 { sign * self.alt_table.performance(alt, k).unwrap() - offset };
+        let mut positive_flow = vec![0.0; self.n];
+        let (mut w, mut r) = (
+            VecDeque::<usize>::new(),
+            VecDeque::from(argsorted_fks.to_owned()),
+        );
+        let mut card_l = 0;
+        let mut sum = 0.0;
+        let (mut low, mut up): (f64, f64);
+        let (mut const_fact, mut last_term): (f64, f64);
+
+        for &idx in argsorted_fks {
+            low = fks(idx) - p;
+            up = fks(idx) - q;
+
+            // Remove elements leaving window
+            while !w.is_empty() && fks(*w.front().unwrap()) <= low {
+                sum -= fks(w.pop_front().unwrap());
+                card_l += 1;
+            }
+
+            // Remove elements leaving the right part
+            while !r.is_empty() && fks(*r.front().unwrap()) <= up {
+                let x = r.pop_front().unwrap();
+                if fks(x) >= low {
+                    w.push_back(x);
+                    sum += fks(x);
+                } else {
+                    card_l += 1;
+                }
+            }
+
+            (const_fact, last_term) = match p != q {
+                true => ((fks(idx) - q) / (p - q), -sum / (p - q)),
+                false => (0.0, 0.0),
+            };
+            positive_flow[idx] =
+                1.0 / self.flow_norm() * (card_l as f64 + w.len() as f64 * const_fact + last_term);
+        }
+        positive_flow
+    }
+
+    pub fn perf(&self, k: usize, i: usize) -> Option<&f64> {
+        self.alt_table.performance(i, k)
+    }
+
+    pub fn fast_neg_unicriterion_flow(
+        &self,
+        k: usize,
+        q: f64,
+        p: f64,
+        argsorted_fks: &[usize],
+    ) -> Vec<f64> {
+        let sign = self.criterion_sign(k);
+        // Evaluations are shifted by the smallest one to keep the running sum small, see the positive flow
+        let offset = argsorted_fks.first().map_or(0.0, |&alt| {
+            sign * self.alt_table.performance(alt, k).unwrap()
+        });
+        let fks =
+            |alt: usize| -> f64 { sign * self.alt_table.performance(alt, k).unwrap() - offset };
+        let mut negative_flows = vec![0.0; self.n];
+        let (mut l, mut w) = (
+            VecDeque::from(argsorted_fks.to_owned()),
+            VecDeque::<usize>::new(),
+        );
+        let mut card_r = 0;
+        let mut sum = 0.0;
+        let (mut low, mut up): (f64, f64);
+        let (mut const_fact, mut last_term): (f64, f64);
+
+        for &idx in argsorted_fks.iter().rev() {
+            low = fks(idx) + q;
+            up = fks(idx) + p;
+
+            // Remove elements leaving window
+            while !w.is_empty() && fks(*w.back().expect("not empty")) >= up {
+                sum -= fks(w.pop_back().expect("not empty"));
+                card_r += 1;
+            }
+
+            // Remove elements leaving the left part
+            while !l.is_empty() && fks(*l.back().expect("not empty")) >= low {
+                let x = l.pop_back().expect("not empty");
+                if fks(x) <= up {
+                    w.push_front(x);
+                    sum += fks(x);
+                } else {
+                    card_r += 1;
+                }
+            }
+
+            if p != q {
+                const_fact = (fks(idx) + q) / (p - q);
+                last_term = sum / (p - q);
+            } else {
+                (const_fact, last_term) = (0.0, 0.0);
+            }
+            negative_flows[idx] = 1.0 / self.flow_norm()
+                * (card_r as f64 - (w.len() as f64 * const_fact) + last_term);
+        }
+        negative_flows
+    }
+
+    /// Compute the unicriterion positive and negative flows for criterion k
+    /// using the O(qnlogn) method from Van Asche, 2018
+    ///
+    /// Ties: an alternative at distance exactly p counts as fully preferred and one at distance
+    /// exactly q (or 0 for the V-Shape) as not preferred at all. Both preference functions are
+    /// continuous at q and p, so the flows match the pairwise method up to rounding errors,
+    /// whatever the order of tied alternatives in the argsort.
+    fn fast_unicriterion_flows(&self, k: usize) -> Option<(Vec<f64>, Vec<f64>)> {
+        if k >= self.q {
+            return None;
+        }
+        let (q, p) = match self.generalized_criteria[k] {
+            GeneralizedCriterion::VShape { p } => (0.0, p),
+            GeneralizedCriterion::Linear { q, p } => (q, p),
+            _ => panic!("Wrong type of criterion for fast method"),
+        };
+        debug_assert!(
+            self.argsort_consistent(k),
+            "Argsort cache of criterion {} does not match the evaluations",
+            k
+        );
+        //
+        // We work with argsort instead of sort to work with usize instead of ints
+        let argsorted_fks = self.argsorted_eval_matrix[k]
+            .as_ref()
+            .expect("to be computed at construction");
+
+        let positive_flow = self.fast_pos_unicriterion_flow(k, q, p, argsorted_fks);
+        let negative_flows = self.fast_neg_unicriterion_flow(k, q, p, argsorted_fks);
+        Some((positive_flow, negative_flows))
+    }
+
+    fn slow_unicriterion_flows<F: FlowFloat>(
+        &self,
+        dist_mat: &[Vec<F>],
+        generalized_criterion: &GeneralizedCriterion,
+    ) -> (Vec<f64>, Vec<f64>) {
+        pairwise_unicriterion_flows(dist_mat, generalized_criterion, self.flow_norm())
+    }
+
+    fn unicriterion_flows(&self, k: usize) -> Option<(Vec<f64>, Vec<f64>)> {
+        self.unicriterion_flows_in::<f64>(k)
+    }
+
+    /// Unicriterion flows of criterion k, where the pairwise method stores the differences and
+    /// accumulates the preference degrees in F
+    fn unicriterion_flows_in<F: FlowFloat>(&self, k: usize) -> Option<(Vec<f64>, Vec<f64>)> {
+        if k >= self.q {
+            panic!("Wrong criterion index used, {}>{}", k, self.q)
+        }
+
+        let generalized_criterion = &self.generalized_criteria[k];
+
+        match generalized_criterion {
+            GeneralizedCriterion::VShape { p: _ } | GeneralizedCriterion::Linear { q: _, p: _ }
+                if !self.has_missing(k) =>
+            {
+                self.fast_unicriterion_flows(k)
+            }
+            _ => {
+                let fks = self.oriented_criterion(k);
+                let dist_mat: Vec<Vec<F>> = fks
+                    .iter()
+                    .map(|&a_i| fks.iter().map(move |&a_j| F::from_f64(a_i - a_j)).collect())
+                    .collect();
+                Some(self.slow_unicriterion_flows(&dist_mat, generalized_criterion))
+            }
+        }
+    }
+
+    /// Positive unicriterion flow of criterion k if `positive`, else the negative one. Only this half is
+    /// computed by the fast method, the pairwise one computing both anyway.
+    fn unicriterion_half_flow(&self, k: usize, positive: bool) -> Vec<f64> {
+        let fast = |q: f64, p: f64| {
+            debug_assert!(
+                self.argsort_consistent(k),
+                "Argsort cache of criterion {} does not match the evaluations",
+                k
+            );
+            let argsorted_fks = self.argsorted_eval_matrix[k]
+                .as_ref()
+                .expect("to be computed at construction");
+            if positive {
+                self.fast_pos_unicriterion_flow(k, q, p, argsorted_fks)
+            } else {
+                self.fast_neg_unicriterion_flow(k, q, p, argsorted_fks)
+            }
+        };
+
+        match self.generalized_criteria[k] {
+            GeneralizedCriterion::VShape { p } if !self.has_missing(k) => fast(0.0, p),
+            GeneralizedCriterion::Linear { q, p } if !self.has_missing(k) => fast(q, p),
+            _ => {
+                let (positive_flows, negative_flows) = self.unicriterion_flows(k).unwrap();
+                if positive {
+                    positive_flows
+                } else {
+                    negative_flows
+                }
+            }
+        }
+    }
+
+    fn solve_half(&self, positive: bool) -> Vec<f64> {
+        if let Err(e) = self.validate() {
+            panic!("Invalid problem: {}", e);
+        }
+        let mut flows = vec![0.0; self.n];
+        for k in 0..self.q {
+            for (flow, unicrit_flow) in flows
+                .iter_mut()
+                .zip(self.unicriterion_half_flow(k, positive))
+            {
+                *flow += self.weights[k] * unicrit_flow;
+            }
+        }
+        flows
+    }
+
+    /// Positive (leaving) flows only, about half the work of `solve`
+    pub fn solve_positive_only(&self) -> Vec<f64> {
+        self.solve_half(true)
+    }
+
+    /// Negative (entering) flows only, about half the work of `solve`
+    pub fn solve_negative_only(&self) -> Vec<f64> {
+        self.solve_half(false)
+    }
+
+    /// Check that the sizes of the parts of the problem agree, which `new` ensures but a
+    /// deserialized problem may not
+    pub fn validate(&self) -> Result<(), PrometheeError> {
+        let check = |field: &str, given: usize, expected: usize| {
+            if given == expected {
+                Ok(())
+            } else {
+                Err(PrometheeError::DimensionMismatch {
+                    field: field.to_string(),
+                    given,
+                    expected,
+                })
+            }
+        };
+
+        check("alternatives", self.alt_table.n(), self.n)?;
+        check("criteria", self.alt_table.q(), self.q)?;
+        check(
+            "criteria directions",
+            self.alt_table.criteria_directions().len(),
+            self.q,
+        )?;
+        check(
+            "criteria names",
+            self.alt_table.criteria_names().len(),
+            self.q,
+        )?;
+        check("weights", self.weights.len(), self.q)?;
+        check(
+            "preference functions",
+            self.generalized_criteria.len(),
+            self.q,
+        )?;
+        check("cached argsorts", self.argsorted_eval_matrix.len(), self.q)?;
+        check_criteria(&self.generalized_criteria)?;
+        for alt in self.alt_table.alternatives() {
+            check("evaluations", alt.perfs().len(), self.q)?;
+        }
+        for argsorted_fks in self.argsorted_eval_matrix.iter().flatten() {
+            check(
+                "alternatives in cached argsort",
+                argsorted_fks.len(),
+                self.n,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Solve the problem, panicking if it is invalid (see `validate`)
+    pub fn solve(&self) -> Promethee2Result {
+        self.try_solve()
+            .unwrap_or_else(|e| panic!("Invalid problem: {}", e))
+    }
+
+    /// Same as `solve`, but returns an error if the problem is invalid
+    pub fn try_solve(&self) -> Result<Promethee2Result, PrometheeError> {
+        self.validate()?;
+        Ok(self.solve_normalized_weights(&self.weights))
+    }
+
+    /// Solve the problem with the given weights instead of the ones of the problem, which is left untouched.
+    /// The weights are normalized so that they sum to one.
+    pub fn solve_with_weights(&self, weights: &[f64]) -> Promethee2Result {
+        if weights.len() != self.q {
+            panic!(
+                "Wrong number of weights given, {} given, {} expected",
+                weights.len(),
+                self.q
+            );
+        }
+
+        let tot_w = check_weight_sum(weights).unwrap_or_else(|e| panic!("{}", e));
+        let normalized_weights: Vec<f64> = weights.iter().map(|w| w / tot_w).collect();
+        self.solve_normalized_weights(&normalized_weights)
+    }
+
+    /// Solve the problem for each of the weight sets, returning the results in the same order.
+    /// The unicriterion flows are computed only once and aggregated with each weight set.
+    pub fn solve_batch(&self, weight_sets: &[Vec<f64>]) -> Vec<Promethee2Result> {
+        if let Some((s, weights)) = weight_sets
+            .iter()
+            .enumerate()
+            .find(|(_, weights)| weights.len() != self.q)
+        {
+            panic!(
+                "Wrong number of weights given in set {}, {} given, {} expected",
+                s,
+                weights.len(),
+                self.q
+            );
+        }
+
+        let unicriterion_flows = self.compute_unicriterion_flows();
+        weight_sets
+            .iter()
+            .map(|weights| unicriterion_flows.aggregate(weights))
+            .collect()
+    }
+
+    fn solve_normalized_weights(&self, weights: &[f64]) -> Promethee2Result {
+        self.solve_normalized_weights_with_progress(weights, |_, _| {})
+    }
+
+    /// Same as `solve`, calling `progress(criteria_done, q)` each time the flows of a criterion are computed,
+    /// e.g. to display a progress bar on large problems
+    pub fn solve_with_progress(&self, progress: impl FnMut(usize, usize)) -> Promethee2Result {
+        if let Err(e) = self.validate() {
+            panic!("Invalid problem: {}", e);
+        }
+        self.solve_normalized_weights_with_progress(&self.weights, progress)
+    }
+
+    fn solve_normalized_weights_with_progress(
+        &self,
+        weights: &[f64],
+        mut progress: impl FnMut(usize, usize),
+    ) -> Promethee2Result {
+        let (unicrit_positive_flows, unicrit_negative_flows) = (0..self.q)
+            .map(|k| {
+                let flows = self.unicriterion_flows(k).unwrap();
+                progress(k + 1, self.q);
+                flows
+            })
+            .unzip();
+        let unicriterion_flows = UnicriterionFlows {
+            unicrit_positive_flows,
+            unicrit_negative_flows,
+        };
+        let (positive_flows, negative_flows) = unicriterion_flows.weighted_sum(weights);
+
+        Promethee2Result {
+            positive_flows,
+            unicrit_positive_flows: unicriterion_flows.unicrit_positive_flows,
+            negative_flows,
+            unicrit_negative_flows: unicriterion_flows.unicrit_negative_flows,
+        }
+    }
+
+    /// Same as `solve`, with the pairwise method used for the Usual, U-Shape and AsymmetricLinear criteria
+    /// working in single precision: the (n, n) matrix of differences takes half the memory, which is
+    /// what limits the size of the problems these criteria can handle. The price is precision: the
+    /// preference degrees are summed in f32, so the flows are exact to about 1e-7 for the 0 or 1 degrees
+    /// of Usual and U-Shape criteria, and the error of fractional degrees grows with n (around 1e-6 for
+    /// 5000 alternatives). The fast method for VShape and Linear criteria is unchanged.
+    pub fn solve_f32(&self) -> Promethee2Result {
+        if let Err(e) = self.validate() {
+            panic!("Invalid problem: {}", e);
+        }
+        let (unicrit_positive_flows, unicrit_negative_flows) = (0..self.q)
+            .map(|k| self.unicriterion_flows_in::<f32>(k).unwrap())
+            .unzip();
+        let unicriterion_flows = UnicriterionFlows {
+            unicrit_positive_flows,
+            unicrit_negative_flows,
+        };
+        let (positive_flows, negative_flows) = unicriterion_flows.weighted_sum(&self.weights);
+
+        Promethee2Result {
+            positive_flows,
+            unicrit_positive_flows: unicriterion_flows.unicrit_positive_flows,
+            negative_flows,
+            unicrit_negative_flows: unicriterion_flows.unicrit_negative_flows,
+        }
+    }
+
+    /// Compute the positive and negative unicriterion flows of every criterion.
+    /// These do not depend on the weights, so they can be computed once and aggregated
+    /// with as many weight vectors as needed through `UnicriterionFlows::aggregate`.
+    pub fn compute_unicriterion_flows(&self) -> UnicriterionFlows {
+        let (unicrit_positive_flows, unicrit_negative_flows) = (0..self.q)
+            .map(|k| self.unicriterion_flows(k).unwrap())
+            .unzip();
+
+        UnicriterionFlows {
+            unicrit_positive_flows,
+            unicrit_negative_flows,
+        }
+    }
+
+    /// Position of each alternative with respect to the ideal alternative, made of the best evaluation on
+    /// every criterion, and the anti-ideal one, made of the worst evaluations, according to the directions.
+    /// Returns, for each alternative, the pair (aggregated preference of the ideal over it, aggregated
+    /// preference of it over the anti-ideal), both between 0 and 1.
+    pub fn ideal_positioning(&self) -> Vec<(f64, f64)> {
+        let mut positions = vec![(0.0, 0.0); self.n];
+        for k in 0..self.q {
+            let fks = self.oriented_criterion(k);
+            let ideal = fks.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let anti_ideal = fks.iter().copied().fold(f64::INFINITY, f64::min);
+            let criterion = &self.generalized_criteria[k];
+            for (position, fki) in positions.iter_mut().zip(&fks) {
+                position.0 += self.weights[k] * criterion.normalisation(ideal - fki);
+                position.1 += self.weights[k] * criterion.normalisation(fki - anti_ideal);
+            }
+        }
+        positions
+    }
+
+    /// Baseline ranking that aggregates before comparing: the alternatives are scored by the weighted
+    /// sum of their evaluations (oriented so that higher is better), and ranked by the net flows of a
+    /// single VShape criterion with threshold p applied to these scores. Unlike Promethee, a large
+    /// advantage on one criterion fully compensates small disadvantages on the others.
+    pub fn aggregate_then_rank(&self, p: f64) -> Vec<usize> {
+        let mut scores = vec![0.0; self.n];
+        for (k, w) in self.weights.iter().enumerate() {
+            for (score, fki) in scores.iter_mut().zip(self.oriented_criterion(k)) {
+                *score += w * fki;
+            }
+        }
+
+        let criterion = GeneralizedCriterion::VShape { p };
+        let net_flows: Vec<f64> = scores
+            .iter()
+            .map(|si| {
+                scores
+                    .iter()
+                    .map(|sj| criterion.sym_normalisation(si - sj))
+                    .sum::<f64>()
+                    / self.flow_norm()
+            })
+            .collect();
+        rank_with_ties(&net_flows, |i, j| i.cmp(j))
+    }
+
+    /// For each criterion, the alternatives ranked in descending order of unicriterion net flow,
+    /// that is the ranking this criterion would produce alone
+    pub fn single_criterion_rankings(&self) -> Vec<Vec<usize>> {
+        (0..self.q)
+            .map(|k| {
+                let (positive_flows, negative_flows) = self.unicriterion_flows(k).unwrap();
+                let net_flows: Vec<f64> = positive_flows
+                    .iter()
+                    .zip(&negative_flows)
+                    .map(|(pos, neg)| pos - neg)
+                    .collect();
+                rank_with_ties(&net_flows, |i, j| i.cmp(j))
+            })
+            .collect()
+    }
+
+    /// For each criterion, the Spearman correlation between the ranking of the result and the ranking the
+    /// criterion would produce alone (see `single_criterion_rankings`). A value close to 1 means that
+    /// the final ranking is mostly driven by this criterion.
+    /// Tied alternatives share the average of their ranks, see `flows_spearman`.
+    pub fn criterion_alignment(&self, result: &Promethee2Result) -> Vec<f64> {
+        let net_flows = result.net_flows();
+        (0..self.q)
+            .map(|k| flows_spearman(&net_flows, &result.unicriterion_net_flows(k)))
+            .collect()
+    }
+
+    /// Solve the problem with the pairwise O(n²q) method for every criterion, and return the
+    /// aggregated preference matrix `pi[a][b]` along with the flows.
+    /// This is meant for inspection, `solve` should be preferred when only the flows are needed.
+    /// With an AsymmetricLinear criterion the negative flows count the losses of each alternative,
+    /// so they are not the column sums of the matrix, which holds the gains.
+    pub fn solve_verbose(&self) -> (Promethee2Result, Vec<Vec<f64>>) {
+        let mut positive_flows: Vec<f64> = vec![0.0; self.n];
+        let mut negative_flows: Vec<f64> = vec![0.0; self.n];
+        let mut positive_unicriterions_flows: Vec<Vec<f64>> = Vec::with_capacity(self.q);
+        let mut negative_unicriterions_flows: Vec<Vec<f64>> = Vec::with_capacity(self.q);
+        let mut preference_matrix: Vec<Vec<f64>> = vec![vec![0.0; self.n]; self.n];
+
+        for k in 0..self.q {
+            let generalized_criterion = &self.generalized_criteria[k];
+            let fks = self.oriented_criterion(k);
+
+            // Unicriterion preference degrees P_k(a_i, a_j)
+            let unicrit_prefs: Vec<Vec<f64>> = fks
+                .iter()
+                .map(|&a_i| {
+                    fks.iter()
+                        .map(|&a_j| generalized_criterion.normalisation(a_i - a_j))
+                        .collect()
+                })
+                .collect();
+
+            let mut pos_unicriterion_flow = vec![0.0; self.n];
+            let mut neg_unicriterion_flow = vec![0.0; self.n];
+            for i in 0..self.n {
+                // Pairs with a missing evaluation are left out of the comparisons, as in `solve`
+                let missing = (0..self.n)
+                    .filter(|&j| j != i && (fks[i] - fks[j]).is_nan())
+                    .count();
+                let norm = self.flow_norm() - missing as f64;
+                for j in (0..self.n).filter(|&j| j != i) {
+                    if norm > 0.0 {
+                        pos_unicriterion_flow[i] += unicrit_prefs[i][j] / norm;
+                        neg_unicriterion_flow[i] +=
+                            generalized_criterion.loss_normalisation(fks[i] - fks[j]) / norm;
+                    }
+                    preference_matrix[i][j] += self.weights[k] * unicrit_prefs[i][j];
+                }
+                positive_flows[i] += self.weights[k] * pos_unicriterion_flow[i];
+                negative_flows[i] += self.weights[k] * neg_unicriterion_flow[i];
+            }
+
+            positive_unicriterions_flows.push(pos_unicriterion_flow);
+            negative_unicriterions_flows.push(neg_unicriterion_flow);
+        }
+
+        (
+            Promethee2Result {
+                positive_flows,
+                unicrit_positive_flows: positive_unicriterions_flows,
+                negative_flows,
+                unicrit_negative_flows: negative_unicriterions_flows,
+            },
+            preference_matrix,
+        )
+    }
+
+    /// Aggregated preference matrix `pi[a][b]`, the weighted sum of the unicriterion preference degrees
+    /// of a over b. This uses the pairwise O(n²q) method.
+    /// An AsymmetricLinear criterion contributes the gain of a over b, see `net_preference_matrix` for its losses.
+    pub fn preference_matrix(&self) -> Vec<Vec<f64>> {
+        let mut preference_matrix = vec![vec![0.0; self.n]; self.n];
+        for k in 0..self.q {
+            let generalized_criterion = &self.generalized_criteria[k];
+            let fks = self.oriented_criterion(k);
+            for (i, row) in preference_matrix.iter_mut().enumerate() {
+                for (j, pi) in row.iter_mut().enumerate().filter(|&(j, _)| j != i) {
+                    *pi += self.weights[k] * generalized_criterion.normalisation(fks[i] - fks[j]);
+                }
+            }
+        }
+        preference_matrix
+    }
+
+    /// Matrix of the pairwise net preferences, the gain of a over b minus its loss against b. This is the
+    /// skew-symmetric `pi[a][b] - pi[b][a]` unless an AsymmetricLinear criterion weighs the losses on their
+    /// own ramp. The sum of row a divided by the flow normalization (see `FlowNormalization`) is the net
+    /// flow of a.
+    pub fn net_preference_matrix(&self) -> Vec<Vec<f64>> {
+        let mut net_matrix = vec![vec![0.0; self.n]; self.n];
+        for k in 0..self.q {
+            let generalized_criterion = &self.generalized_criteria[k];
+            let fks = self.oriented_criterion(k);
+            for (i, row) in net_matrix.iter_mut().enumerate() {
+                for (j, net) in row.iter_mut().enumerate().filter(|&(j, _)| j != i) {
+                    *net +=
+                        self.weights[k] * generalized_criterion.sym_normalisation(fks[i] - fks[j]);
+                }
+            }
+        }
+        net_matrix
+    }
+
+    /// Aggregated preference degree `pi(a, b)` of alternative a over alternative b, computed in O(q)
+    pub fn pairwise_preference(&self, a: usize, b: usize) -> f64 {
+        if a >= self.n || b >= self.n {
+            panic!(
+                "Alternative index out of range, ({}, {}) given, {} alternatives",
+                a, b, self.n
+            );
+        }
+
+        (0..self.q)
+            .map(|k| {
+                let d = self.criterion_sign(k)
+                    * (self.alt_table.performance(a, k).unwrap()
+                        - self.alt_table.performance(b, k).unwrap());
+                self.weights[k] * self.generalized_criteria[k].normalisation(d)
+            })
+            .sum()
+    }
+
+    /// Average aggregated preference degree `pi(a, b)` over the pairs of an alternative a of group_a and
+    /// an alternative b of group_b, to compare two groups of alternatives as a whole.
+    /// Comparing `group_preference(A, B)` with `group_preference(B, A)` tells which group is preferred.
+    pub fn group_preference(&self, group_a: &[usize], group_b: &[usize]) -> f64 {
+        if group_a.is_empty() || group_b.is_empty() {
+            panic!(
+                "Groups must not be empty, {} and {} alternatives given",
+                group_a.len(),
+                group_b.len()
+            );
+        }
+        if let Some(i) = group_a.iter().chain(group_b).find(|&&i| i >= self.n) {
+            panic!(
+                "Alternative index out of range, {} given, {} alternatives",
+                i, self.n
+            );
+        }
+
+        let total: f64 = group_a
+            .iter()
+            .cartesian_product(group_b)
+            .map(|(&a, &b)| self.pairwise_preference(a, b))
+            .sum();
+        total / (group_a.len() * group_b.len()) as f64
+    }
+
+    /// Contribution `w_k * P_k(a, b)` of each criterion to the preference degree of a over b, the gain of a
+    /// for an AsymmetricLinear criterion as in `preference_matrix`
+    pub fn pairwise_breakdown(&self, a: usize, b: usize) -> Vec<f64> {
+        if a >= self.n || b >= self.n {
+            panic!(
+                "Alternative index out of range, ({}, {}) given, {} alternatives",
+                a, b, self.n
+            );
+        }
+
+        (0..self.q)
+            .map(|k| {
+                let d = self.criterion_sign(k)
+                    * (self.alt_table.performance(a, k).unwrap()
+                        - self.alt_table.performance(b, k).unwrap());
+                self.weights[k] * self.generalized_criteria[k].normalisation(d)
+            })
+            .collect()
+    }
+
+    /// For each criterion k, the sum of the preference degrees `P_k(ai, aj)` of alternative ai over all the
+    /// other alternatives, neither weighted nor normalized. Dividing by the flow normalization (see
+    /// `FlowNormalization`) and summing with the weights gives the positive flow of ai.
+    pub fn leaving_flow_contributions(&self, ai: usize) -> Vec<f64> {
+        if ai >= self.n {
+            panic!(
+                "Alternative index out of range, {} given, {} alternatives",
+                ai, self.n
+            );
+        }
+
+        (0..self.q)
+            .map(|k| {
+                let fks = self.oriented_criterion(k);
+                fks.iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != ai)
+                    .map(|(_, fkj)| self.generalized_criteria[k].normalisation(fks[ai] - fkj))
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Shannon entropy (natural logarithm) of the contributions of the criteria to the preference of a over b,
+    /// normalized to sum to one. It is 0 when a single criterion makes the whole preference and ln(q)
+    /// when all the criteria contribute equally. It is also 0 when a is not preferred to b at all.
+    pub fn pairwise_entropy(&self, a: usize, b: usize) -> f64 {
+        let breakdown = self.pairwise_breakdown(a, b);
+        let total: f64 = breakdown.iter().sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        -breakdown
+            .iter()
+            .map(|contribution| contribution / total)
+            .filter(|&p| p > 0.0)
+            .map(|p| p * p.ln())
+            .sum::<f64>()
+    }
+
+    /// Positive and negative flows of each alternative computed against the alternatives of a reference
+    /// table only, e.g. the profiles of FlowSort, normalized by the number of reference alternatives
+    pub fn flows_against(&self, reference: &AlternativeTable) -> (Vec<f64>, Vec<f64>) {
+        if reference.q() != self.q {
+            panic!(
+                "Wrong number of criteria in the reference table, {} given, {} expected",
+                reference.q(),
+                self.q
+            );
+        }
+        if reference.criteria_directions() != self.alt_table.criteria_directions() {
+            panic!("Criteria directions of the reference table differ from the problem ones");
+        }
+
+        let n_ref = reference.n() as f64;
+        let mut positive_flows = vec![0.0; self.n];
+        let mut negative_flows = vec![0.0; self.n];
+        for k in 0..self.q {
+            let generalized_criterion = &self.generalized_criteria[k];
+            let fks = self.oriented_criterion(k);
+            let sign = self.criterion_sign(k);
+            let refs: Vec<f64> = reference
+                .criterion(k)
+                .unwrap()
+                .into_iter()
+                .map(|f| sign * f)
+                .collect();
+
+            for (i, fki) in fks.iter().enumerate() {
+                for fkr in &refs {
+                    positive_flows[i] +=
+                        self.weights[k] * generalized_criterion.normalisation(fki - fkr) / n_ref;
+                    negative_flows[i] += self.weights[k]
+                        * generalized_criterion.loss_normalisation(fki - fkr)
+                        / n_ref;
+                }
+            }
+        }
+        (positive_flows, negative_flows)
+    }
+
+    /// Criterion contributing the most to the advantage of a over b, i.e. maximizing
+    /// `w_k * (P_k(a, b) - P_k(b, a))`, or None if a is not preferred to b overall
+    pub fn decisive_criterion(&self, a: usize, b: usize) -> Option<usize> {
+        let for_a = self.pairwise_breakdown(a, b);
+        let for_b = self.pairwise_breakdown(b, a);
+        if for_a.iter().sum::<f64>() <= for_b.iter().sum::<f64>() {
+            return None;
+        }
+
+        for_a
+            .iter()
+            .zip(&for_b)
+            .map(|(pi_ab, pi_ba)| pi_ab - pi_ba)
+            .enumerate()
+            .max_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+            .map(|(k, _)| k)
+    }
+
+    /// Build the ranking of the alternatives from a result of this problem, with their names and net flows
+    pub fn ranking_table(&self, result: &Promethee2Result) -> Ranking {
+        let net_flows = result.net_flows();
+        Ranking {
+            entries: result
+                .ranked_alts()
+                .into_iter()
+                .map(|i| RankingEntry {
+                    index: i,
+                    name: self.alt_name(i).unwrap().to_string(),
+                    net_flow: net_flows[i],
+                })
+                .collect(),
+        }
+    }
+
+    /// Rows of a results table with the rank, name and flows of each alternative, in descending
+    /// order of net flow. Alternatives whose net flows are tied get the same rank.
+    pub fn full_ranking(&self, result: &Promethee2Result) -> Vec<RankRow> {
+        let net_flows = result.net_flows();
+        let mut rows: Vec<RankRow> = Vec::with_capacity(self.n);
+        for (pos, i) in result.ranked_alts().into_iter().enumerate() {
+            let rank = match rows.last() {
+                Some(prev) if flows_equal(prev.net_flow, net_flows[i]) => prev.rank,
+                _ => pos + 1,
+            };
+            rows.push(RankRow {
+                rank,
+                index: i,
+                name: self.alt_name(i).unwrap().to_string(),
+                net_flow: net_flows[i],
+                positive_flow: result.positive_flows[i],
+                negative_flow: result.negative_flows[i],
+            });
+        }
+        rows
+    }
+
+    /// Indices of the alternatives left once those of `exclude` are removed, in ascending order.
+    /// Position i of a result of `solve_excluding` corresponds to alternative `retained_indices(exclude)[i]`.
+    pub fn retained_indices(&self, exclude: &[usize]) -> Vec<usize> {
+        if let Some(ai) = exclude.iter().find(|&&ai| ai >= self.n) {
+            panic!("Wrong alternative index excluded, {}>={}", ai, self.n)
+        }
+        (0..self.n).filter(|ai| !exclude.contains(ai)).collect()
+    }
+
+    /// Solve the problem as if the alternatives of `exclude` were not part of it, without building
+    /// a reduced problem. The flows are normalized as in `solve` (see `FlowNormalization`) for the number of
+    /// retained alternatives, and indexed by position among them (see `retained_indices`).
+    /// This uses the pairwise method on the retained alternatives.
+    pub fn solve_excluding(&self, exclude: &[usize]) -> Promethee2Result {
+        let retained = self.retained_indices(exclude);
+        let m = retained.len();
+        let norm = if m > 1 { self.flow_norm_among(m) } else { 1.0 };
+
+        let mut unicrit_positive_flows = Vec::with_capacity(self.q);
+        let mut unicrit_negative_flows = Vec::with_capacity(self.q);
+        for k in 0..self.q {
+            let fks = self.oriented_criterion(k);
+            let dist_mat: Vec<Vec<f64>> = retained
+                .iter()
+                .map(|&ai| retained.iter().map(|&aj| fks[ai] - fks[aj]).collect())
+                .collect();
+            let (pos_flow, neg_flow) =
+                pairwise_unicriterion_flows(&dist_mat, &self.generalized_criteria[k], norm);
+            unicrit_positive_flows.push(pos_flow);
+            unicrit_negative_flows.push(neg_flow);
+        }
+
+        UnicriterionFlows {
+            unicrit_positive_flows,
+            unicrit_negative_flows,
+        }
+        .aggregate(&self.weights)
+    }
+
+    /// Solve the problem with only the given criteria, their weights being normalized among them
+    pub fn solve_subset(&self, criteria: &[usize]) -> Promethee2Result {
+        self.solve_with_weights(&self.subset_weights(criteria))
+    }
+
+    /// Weights of the problem restricted to the given criteria, the other ones being zero
+    fn subset_weights(&self, criteria: &[usize]) -> Vec<f64> {
+        let mut weights = vec![0.0; self.q];
+        for &k in criteria {
+            if k >= self.q {
+                panic!("Wrong criterion index used, {}>{}", k, self.q)
+            }
+            weights[k] = self.weights[k];
+        }
+        weights
+    }
+
+    /// Small subset of criteria, in ascending order, whose ranking has the same top_k alternatives
+    /// (as a set) as the ranking with all criteria, the weights being normalized among the subset.
+    /// This is a greedy heuristic: criteria are removed one at a time, lightest first, as long as the
+    /// top alternatives are unchanged. The subset is minimal in the sense that no single criterion can
+    /// be removed from it, but a smaller subset may exist.
+    pub fn minimal_criteria_for_top(&self, top_k: usize) -> Vec<usize> {
+        let unicriterion_flows = self.compute_unicriterion_flows();
+        let top_of = |criteria: &[usize]| -> Vec<usize> {
+            let weights = self.subset_weights(criteria);
+            if check_weight_sum(&weights).is_err() {
+                return Vec::new();
+            }
+            let mut top: Vec<usize> = unicriterion_flows
+                .aggregate(&weights)
+                .ranked_alts()
+                .into_iter()
+                .take(top_k)
+                .collect();
+            top.sort_unstable();
+            top
+        };
+
+        let mut subset: Vec<usize> = (0..self.q).collect();
+        let reference = top_of(&subset);
+        let by_weight: Vec<usize> = (0..self.q)
+            .sorted_by(|&k, &l| self.weights[k].partial_cmp(&self.weights[l]).unwrap())
+            .collect();
+
+        let mut removed = true;
+        while removed && subset.len() > 1 {
+            removed = false;
+            for &k in &by_weight {
+                let Some(pos) = subset.iter().position(|&l| l == k) else {
+                    continue;
+                };
+                let candidate: Vec<usize> = subset.iter().copied().filter(|&l| l != k).collect();
+                if !candidate.is_empty() && top_of(&candidate) == reference {
+                    subset.remove(pos);
+                    removed = true;
+                }
+            }
+        }
+        subset
+    }
+
+    /// Search for weights under which alternative ai is ranked first, strictly above every other
+    /// alternative, or None if there are none. The net flows being linear in the weights, this solves
+    /// the linear program maximizing the smallest margin between ai and the other alternatives on the
+    /// weights simplex. The returned weights are the ones giving the largest margin.
+    pub fn best_weights_for(&self, ai: usize) -> Option<Vec<f64>> {
+        if ai >= self.n {
+            panic!("Wrong alternative index used, {}>={}", ai, self.n)
+        }
+        if self.n == 1 {
+            return Some(self.weights.clone());
+        }
+        let unicriterion_flows = self.compute_unicriterion_flows();
+        let unicrit_net_flow = |k: usize, i: usize| {
+            unicriterion_flows.unicrit_positive_flows[k][i]
+                - unicriterion_flows.unicrit_negative_flows[k][i]
+        };
+
+        let mut lp = minilp::Problem::new(minilp::OptimizationDirection::Maximize);
+        let weights: Vec<minilp::Variable> =
+            (0..self.q).map(|_| lp.add_var(0.0, (0.0, 1.0))).collect();
+        let margin = lp.add_var(1.0, (f64::NEG_INFINITY, f64::INFINITY));
+
+        let simplex: Vec<(minilp::Variable, f64)> = weights.iter().map(|&w| (w, 1.0)).collect();
+        lp.add_constraint(&simplex, minilp::ComparisonOp::Eq, 1.0);
+        for j in (0..self.n).filter(|&j| j != ai) {
+            let mut beats_j: Vec<(minilp::Variable, f64)> = weights
+                .iter()
+                .enumerate()
+                .map(|(k, &w)| (w, unicrit_net_flow(k, ai) - unicrit_net_flow(k, j)))
+                .collect();
+            beats_j.push((margin, -1.0));
+            lp.add_constraint(&beats_j, minilp::ComparisonOp::Ge, 0.0);
+        }
+
+        let solution = lp.solve().ok()?;
+        if solution[margin] > FLOW_EPS {
+            Some(weights.iter().map(|&w| solution[w]).collect())
+        } else {
+            None
+        }
+    }
+
+    /// Preference threshold p of criterion k, the other parameters being fixed, at which alternatives
+    /// a and b have the same net flow. If several thresholds tie them, the closest one to the current p
+    /// is returned. Only applies to VShape and Linear criteria (for which q stays fixed), None is
+    /// returned for other criteria or if no threshold ties a and b.
+    ///
+    /// Each unicriterion preference degree is `(|d| - q) / (p - q)` until p reaches |d|, and constant
+    /// otherwise. Between two consecutive differences |d|, the net flow difference of a and b is thus
+    /// `A + B / (p - q)`, whose root is computed exactly.
+    pub fn break_even_p(&self, k: usize, a: usize, b: usize) -> Option<f64> {
+        if k >= self.q || a >= self.n || b >= self.n {
+            panic!(
+                "Index out of range, criterion {} and alternatives ({}, {}) given, {} criteria and {} alternatives",
+                k, a, b, self.q, self.n
+            );
+        }
+        let (q, current_p) = match self.generalized_criteria[k] {
+            GeneralizedCriterion::VShape { p } => (0.0, p),
+            GeneralizedCriterion::Linear { q, p } => (q, p),
+            _ => return None,
+        };
+        if a == b {
+            return None;
+        }
+
+        let result = self.solve();
+        let other_diff: f64 = (0..self.q)
+            .filter(|&k2| k2 != k)
+            .map(|k2| {
+                self.weights[k2]
+                    * (result.unicriterion_net_flow(k2, a).unwrap()
+                        - result.unicriterion_net_flow(k2, b).unwrap())
+            })
+            .sum();
+        let fks = self.oriented_criterion(k);
+        let net_diff = |p: f64| {
+            let criterion = GeneralizedCriterion::Linear { q, p };
+            let unicrit_net_flow = |i: usize| {
+                fks.iter()
+                    .map(|fkj| criterion.sym_normalisation(fks[i] - fkj))
+                    .sum::<f64>()
+                    / self.flow_norm()
+            };
+            other_diff + self.weights[k] * (unicrit_net_flow(a) - unicrit_net_flow(b))
+        };
+
+        // Differences |d| > q at which a preference degree of a or b saturates
+        let mut breakpoints: Vec<f64> = [fks[a], fks[b]]
+            .iter()
+            .flat_map(|fki| fks.iter().map(move |fkj| (fki - fkj).abs()))
+            .filter(|&d| d > q)
+            .collect();
+        breakpoints.sort_by(|d1, d2| d1.partial_cmp(d2).unwrap());
+        breakpoints.dedup();
+        let (&first, &last) = (breakpoints.first()?, breakpoints.last()?);
+
+        let mut intervals: Vec<(f64, f64)> = vec![(q, first)];
+        intervals.extend(breakpoints.windows(2).map(|w| (w[0], w[1])));
+        intervals.push((last, f64::INFINITY));
+
+        let closest = |low: f64, up: f64| current_p.clamp(low, up);
+        intervals
+            .into_iter()
+            .filter_map(|(low, up)| {
+                // Two points inside the interval to identify A and B, with g = 1 / (p - q)
+                let (p1, p2) = if up.is_finite() {
+                    (low + (up - low) / 3.0, low + 2.0 * (up - low) / 3.0)
+                } else {
+                    (low + 1.0, low + 2.0)
+                };
+                let (g1, g2) = (1.0 / (p1 - q), 1.0 / (p2 - q));
+                let (d1, d2) = (net_diff(p1), net_diff(p2));
+                let slope = (d1 - d2) / (g1 - g2);
+                let intercept = d1 - slope * g1;
+                if flows_equal(slope, 0.0) {
+                    // Constant difference on the interval, tied everywhere or nowhere
+                    return flows_equal(intercept, 0.0).then(|| closest(low, up));
+                }
+                let g = -intercept / slope;
+                let p = q + 1.0 / g;
+                (g > 0.0 && p >= low && p <= up).then_some(p)
+            })
+            .min_by(|p1, p2| {
+                (p1 - current_p)
+                    .abs()
+                    .partial_cmp(&(p2 - current_p).abs())
+                    .unwrap()
+            })
+    }
+
+    /// Smallest change of the (normalized) weights, in L1 norm, bringing alternatives a and b to a tie,
+    /// the boundary beyond which their order is reversed. Returns the size of the change and the new
+    /// weights, or None if they are already tied or if no weights can reverse their order.
+    /// The net flow difference being linear in the weights, this solves a linear program.
+    pub fn min_weight_change_to_swap(&self, a: usize, b: usize) -> Option<(f64, Vec<f64>)> {
+        if a >= self.n || b >= self.n {
+            panic!(
+                "Alternative index out of range, ({}, {}) given, {} alternatives",
+                a, b, self.n
+            );
+        }
+        let result = self.solve();
+        let diffs: Vec<f64> = (0..self.q)
+            .map(|k| {
+                result.unicriterion_net_flow(k, a).unwrap()
+                    - result.unicriterion_net_flow(k, b).unwrap()
+            })
+            .collect();
+        let net_diff: f64 = diffs.iter().zip(&self.weights).map(|(d, w)| d * w).sum();
+        if flows_equal(net_diff, 0.0) || diffs.iter().all(|d| d * net_diff >= 0.0) {
+            return None;
+        }
+
+        // Minimize sum_k u_k with u_k >= |w'_k - w_k|, on the simplex and the tie hyperplane
+        let mut lp = minilp::Problem::new(minilp::OptimizationDirection::Minimize);
+        let new_weights: Vec<minilp::Variable> =
+            (0..self.q).map(|_| lp.add_var(0.0, (0.0, 1.0))).collect();
+        let changes: Vec<minilp::Variable> = (0..self.q)
+            .map(|_| lp.add_var(1.0, (0.0, f64::INFINITY)))
+            .collect();
+
+        let simplex: Vec<(minilp::Variable, f64)> = new_weights.iter().map(|&w| (w, 1.0)).collect();
+        lp.add_constraint(&simplex, minilp::ComparisonOp::Eq, 1.0);
+        let tie: Vec<(minilp::Variable, f64)> = new_weights
+            .iter()
+            .zip(&diffs)
+            .map(|(&w, &d)| (w, d))
+            .collect();
+        lp.add_constraint(&tie, minilp::ComparisonOp::Eq, 0.0);
+        for k in 0..self.q {
+            let (w, u) = (new_weights[k], changes[k]);
+            lp.add_constraint(
+                [(u, 1.0), (w, -1.0)],
+                minilp::ComparisonOp::Ge,
+                -self.weights[k],
+            );
+            lp.add_constraint(
+                [(u, 1.0), (w, 1.0)],
+                minilp::ComparisonOp::Ge,
+                self.weights[k],
+            );
+        }
+
+        let solution = lp.solve().ok()?;
+        Some((
+            solution.objective(),
+            new_weights.iter().map(|&w| solution[w]).collect(),
+        ))
+    }
+
+    /// Number of times each alternative is ranked first over `samples` weight vectors drawn
+    /// uniformly on the simplex, reproducible for a given seed
+    fn sample_first_ranks(&self, samples: usize, seed: u64) -> Vec<usize> {
+        let unicriterion_flows = self.compute_unicriterion_flows();
+        let mut rng = Lcg(seed);
+        let mut first_counts = vec![0; self.n];
+        for _ in 0..samples {
+            let result = unicriterion_flows.aggregate(&rng.next_weights(self.q));
+            first_counts[result.ranked_alts()[0]] += 1;
+        }
+        first_counts
+    }
+
+    /// Alternatives that are never ranked first under the sampled weight vectors
+    pub fn never_winner(&self, samples: usize, seed: u64) -> Vec<usize> {
+        let first_counts = self.sample_first_ranks(samples, seed);
+        (0..self.n).filter(|&i| first_counts[i] == 0).collect()
+    }
+
+    /// Alternatives ranked first under at least one of the sampled weight vectors
+    pub fn sometimes_winner(&self, samples: usize, seed: u64) -> Vec<usize> {
+        let first_counts = self.sample_first_ranks(samples, seed);
+        (0..self.n).filter(|&i| first_counts[i] > 0).collect()
+    }
+
+    /// Compute the Promethee I partial preorder from the positive and negative flows
+    pub fn solve_partial(&self) -> Promethee1Result {
+        let result = self.solve();
+        Promethee1Result::new(result.positive_flows, result.negative_flows)
+    }
+
+    /// Same as `solve_partial`, with flows differing by at most eps considered equal
+    pub fn solve_partial_eps(&self, eps: f64) -> Promethee1Result {
+        let result = self.solve();
+        Promethee1Result::with_eps(result.positive_flows, result.negative_flows, eps)
+    }
+
+    #[deprecated(note = "Panics for criteria other than VShape, use `thresholds` instead")]
+    pub fn get_parameter(&self, k: usize) -> f64 {
+        match self.generalized_criteria[k] {
+            crate::generalized_criterion::GeneralizedCriterion::VShape { p } => p,
+            _ => panic!("Invalid criterion"),
+        }
+    }
+
+    /// Return the indifference threshold q and preference threshold p of criterion k, if they exist.
+    /// By convention, the threshold of a UShape criterion is returned as an indifference threshold,
+    /// and only the gain thresholds of an AsymmetricLinear criterion are returned.
+    pub fn thresholds(&self, k: usize) -> Option<(Option<f64>, Option<f64>)> {
+        match self.generalized_criteria.get(k)? {
+            GeneralizedCriterion::Usual => Some((None, None)),
+            GeneralizedCriterion::VShape { p } => Some((None, Some(*p))),
+            GeneralizedCriterion::Linear { q, p } => Some((Some(*q), Some(*p))),
+            GeneralizedCriterion::UShape { p } => Some((Some(*p), None)),
+            GeneralizedCriterion::AsymmetricLinear { q_pos, p_pos, .. } => {
+                Some((Some(*q_pos), Some(*p_pos)))
+            }
+        }
+    }
+
+    /// Return the evaluation of the alternatives for criterion k, sorted in ascending order
+    /// If the evaluation matrix is not sorted, compute it. Missing (NaN) evaluations come last.
+    pub fn sorted_evals(&self, k: usize) -> Vec<f64> {
+        self.sorted_with_indices(k)
+            .into_iter()
+            .map(|(_, f)| f)
+            .collect()
+    }
+
+    /// Return the pairs (alternative index, evaluation) for criterion k, sorted in ascending order of evaluation.
+    /// The cached argsort is used if available, otherwise the sort is computed and missing (NaN) evaluations come last.
+    pub fn sorted_with_indices(&self, k: usize) -> Vec<(usize, f64)> {
+        let with_perf = |&i: &usize| (i, self.perf(k, i).unwrap().to_owned());
+        match self.argsorted_eval_matrix[k].as_ref() {
+            // Cache is in descending order of true evaluations for Min criteria with preserved signs
+            Some(sorted_indices) if self.criterion_sign(k) < 0.0 => {
+                sorted_indices.iter().rev().map(with_perf).collect()
+            }
+            Some(sorted_indices) => sorted_indices.iter().map(with_perf).collect(),
+            None => {
+                let mut sorted_fks: Vec<(usize, f64)> =
+                    (0..self.n).map(|i| with_perf(&i)).collect();
+                sorted_fks.sort_by(|(_, f_i), (_, f_j)| {
+                    f_i.is_nan()
+                        .cmp(&f_j.is_nan())
+                        .then_with(|| f_i.total_cmp(f_j))
+                });
+                sorted_fks
+            }
+        }
+    }
+
+    /// Return the pairs (alternative name, evaluation) for criterion k, from the most to the least
+    /// preferred evaluation according to the direction of the criterion, missing (NaN) evaluations last
+    pub fn sorted_by_criterion(&self, k: usize) -> Vec<(&str, f64)> {
+        let mut sorted = self.sorted_with_indices(k);
+        if self.criterion_sign(k) > 0.0 {
+            let n_given = sorted.iter().filter(|(_, f)| !f.is_nan()).count();
+            sorted[..n_given].reverse();
+        }
+        sorted
+            .into_iter()
+            .map(|(i, f)| (self.alt_name(i).unwrap(), f))
+            .collect()
+    }
+
+    /// Shift the evaluation of alternative i for criterion k by shift
+    /// The argsort cache of criterion k, if any, is updated by moving alternative i to its new position, in O(n)
+    pub fn shift_eval(&mut self, k: usize, i: usize, shift: f64) {
+        self.check_eval_indices(k, i);
+        self.alt_table.shift_performance(i, k, shift);
+        self.reposition_in_cache(k, i);
+    }
+
+    /// Set the evaluation of alternative i for criterion k, updating the argsort cache like `shift_eval`
+    pub fn set_eval(&mut self, k: usize, i: usize, val: f64) {
+        self.check_eval_indices(k, i);
+        self.alt_table.set_performance(i, k, val);
+        self.reposition_in_cache(k, i);
+    }
+
+    /// Panic if criterion k or alternative i does not exist, before an evaluation is changed
+    fn check_eval_indices(&self, k: usize, i: usize) {
+        if k >= self.q {
+            panic!("Wrong criterion index used, {}>={}", k, self.q)
+        }
+        if i >= self.n {
+            panic!("Wrong alternative index used, {}>={}", i, self.n)
+        }
+    }
+
+    /// Apply f to the evaluations of criterion k (see `AlternativeTable::transform_criterion`) and sort them again
+    /// in the argsort cache, f not being necessarily increasing
+    pub fn transform_criterion(&mut self, k: usize, f: impl Fn(f64) -> f64) {
+        self.alt_table.transform_criterion(k, f);
+        self.rebuild_criterion_cache(k);
+    }
+
+    /// Round every evaluation to the given number of decimals (see `AlternativeTable::round_evaluations`)
+    /// and rebuild the argsort cache
+    pub fn round_evaluations(&mut self, decimals: u32) {
+        self.alt_table.round_evaluations(decimals);
+        self.rebuild_cache();
+    }
+
+    /// Move alternative i to its position in the argsort cache of criterion k, if any, after its evaluation changed.
+    /// The cache is dropped if the new evaluation is missing, the flows of the criterion are then computed pairwise,
+    /// and built again once no evaluation is missing.
+    fn reposition_in_cache(&mut self, k: usize, i: usize) {
+        let sign = self.criterion_sign(k);
+        let alt_table = &self.alt_table;
+        if alt_table.performance(i, k).is_some_and(|f| f.is_nan()) {
+            self.argsorted_eval_matrix[k] = None;
+        } else if self.argsorted_eval_matrix[k].is_none() {
+            self.rebuild_criterion_cache(k);
+        } else if let Some(argsorted_fks) = &mut self.argsorted_eval_matrix[k] {
+            let fks = |alt: usize| -> f64 { sign * alt_table.performance(alt, k).unwrap() };
+
+            let old_pos = argsorted_fks.iter().position(|&alt| alt == i).unwrap();
+            argsorted_fks.remove(old_pos);
+            let fi = fks(i);
+            let new_pos = argsorted_fks.partition_point(|&alt| fks(alt) < fi);
+            argsorted_fks.insert(new_pos, i);
+        }
+    }
+
+    pub fn print(&self) {
+        println!(
+            "Promethee problem with {} alternatives and {} criteria",
+            self.n, self.q
+        );
+        let mut table = self.table_builder().build();
+        table.with(Style::modern());
+
+        println!("{}", table);
+    }
+
+    /// Render the problem as a Markdown table, with the same rows as `print`
+    pub fn to_markdown(&self) -> String {
+        let mut table = self.table_builder().build();
+        table.with(Style::markdown());
+        table.to_string()
+    }
+
+    /// Rows of the table of the problem: criteria, directions, weights, preference functions and alternatives
+    fn table_builder(&self) -> tabled::builder::Builder {
+        let mut builder = tabled::builder::Builder::default();
+        let crit_names = self.alt_table.criteria_names();
+
+        use std::iter::once;
+
+        builder.push_record(
+            once("Criteria")
+                .chain(crit_names.iter().map(|s| s.as_ref()))
+                .collect::<Vec<_>>(),
+        );
+
+        builder.push_record(
+            once("Directions")
+                .chain(
+                    self.alt_table
+                        .criteria_directions()
+                        .iter()
+                        .map(|d| match d {
+                            OptimizationDirection::Min => "Min",
+                            OptimizationDirection::Max => "Max",
+                        }),
+                )
+                .collect::<Vec<_>>(),
+        );
+
+        builder.push_record(
+            once("Weights".to_string())
+                .chain(self.weights.iter().map(|&w| w.to_string()))
+                .collect::<Vec<_>>(),
+        );
+
+        builder.push_record(
+            once("Preference functions".to_string())
+                .chain(self.generalized_criteria.iter().map(|c| match c {
+                    GeneralizedCriterion::UShape { p } => format!("UShape({})", p),
+                    GeneralizedCriterion::VShape { p } => format!("VShape({})", p),
+                    GeneralizedCriterion::Linear { q, p } => format!("Linear({}, {})", q, p),
+                    GeneralizedCriterion::Usual => "Usual".to_string(),
+                    GeneralizedCriterion::AsymmetricLinear {
+                        q_pos,
+                        p_pos,
+                        q_neg,
+                        p_neg,
+                    } => format!(
+                        "AsymmetricLinear({}, {}, {}, {})",
+                        q_pos, p_pos, q_neg, p_neg
+                    ),
+                }))
+                .collect::<Vec<_>>(),
+        );
+
+        for alternative in self.alt_table.alternatives() {
+            builder.push_record(
+                once(alternative.name().to_string())
+                    .chain(alternative.perfs().iter().map(|&v| v.to_string()))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        builder
+    }
+}
+
+/// Linear congruential generator, enough for the reproducible sampling of weights and test data.
+/// This is the single pseudo-random source of the crate.
+struct Lcg(u64);
+
+impl Lcg {
+    /// Uniform number in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Weights drawn uniformly on the simplex, as normalized exponential variables
+    fn next_weights(&mut self, q: usize) -> Vec<f64> {
+        let weights: Vec<f64> = (0..q).map(|_| -(1.0 - self.next_f64()).ln()).collect();
+        let tot_w: f64 = weights.iter().sum();
+        weights.iter().map(|w| w / tot_w).collect()
+    }
+}
+
+/// 64-bit FNV-1a hash, whose result only depends on the bytes fed, in order, across runs and platforms
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_u64(&mut self, x: u64) {
+        self.write(&x.to_le_bytes());
+    }
+
+    fn write_f64(&mut self, x: f64) {
+        self.write_u64(x.to_bits());
+    }
+
+    /// Strings are prefixed by their length so that consecutive strings cannot be confused
+    fn write_str(&mut self, s: &str) {
+        self.write_u64(s.len() as u64);
+        self.write(s.as_bytes());
+    }
+}
+
+/// Check, in debug builds, that a preference degree is in [0, 1], so that the flows stay in their bounds
+/// Positive and negative unicriterion flows from the matrix of the differences of evaluations `d_ij`, the
+/// sums of preference degrees of each alternative being divided by flow_norm, minus the number of comparisons
+/// left out for a missing evaluation
+fn pairwise_unicriterion_flows<F: FlowFloat>(
+    dist_mat: &[Vec<F>],
+    generalized_criterion: &GeneralizedCriterion,
+    flow_norm: f64,
+) -> (Vec<f64>, Vec<f64>) {
+    let f_flow_norm = F::from_f64(flow_norm);
+    let checked = |pref: f64| {
+        debug_assert_preference_degree(pref, generalized_criterion);
+        F::from_f64(pref)
+    };
+    dist_mat
+        .iter()
+        .enumerate()
+        .map(|(i, di)| {
+            // An alternative is not compared to itself, whatever the preference degree P(0),
+            // nor to the alternatives with which one of the evaluations is missing
+            let (pos, neg, missing): (F, F, usize) = (*di)
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, &dij)| {
+                    let dij: f64 = dij.into();
+                    if dij.is_nan() {
+                        (F::default(), F::default(), 1)
+                    } else {
+                        (
+                            checked(generalized_criterion.normalisation(dij)),
+                            checked(generalized_criterion.loss_normalisation(dij)),
+                            0,
+                        )
+                    }
+                })
+                .fold(
+                    (F::default(), F::default(), 0),
+                    |(acc_p, acc_neg, acc_missing), (pos, neg, missing)| {
+                        (acc_p + pos, acc_neg + neg, acc_missing + missing)
+                    },
+                );
+            if missing == 0 {
+                ((pos / f_flow_norm).into(), (neg / f_flow_norm).into())
+            } else {
+                let norm = flow_norm - missing as f64;
+                if norm > 0.0 {
+                    let norm = F::from_f64(norm);
+                    ((pos / norm).into(), (neg / norm).into())
+                } else {
+                    (0.0, 0.0)
+                }
+            }
+        })
+        .unzip()
+}
+
+fn debug_assert_preference_degree(pref: f64, generalized_criterion: &GeneralizedCriterion) {
+    debug_assert!(
+        (0.0..=1.0).contains(&pref),
+        "Preference degree {} outside [0, 1] for criterion {:?}",
+        pref,
+        generalized_criterion
+    );
+}
+
+/// Check the thresholds of every preference function
+fn check_criteria(generalized_criteria: &[GeneralizedCriterion]) -> Result<(), PrometheeError> {
+    for (k, criterion) in generalized_criteria.iter().enumerate() {
+        criterion
+            .validate()
+            .map_err(|message| PrometheeError::InvalidThresholds {
+                criterion: k,
+                message,
+            })?;
+    }
+    Ok(())
+}
+
+/// Floating point type used by the pairwise method for the differences and the sums of preference degrees
+trait FlowFloat:
+    Copy + Default + std::ops::Add<Output = Self> + std::ops::Div<Output = Self> + Into<f64>
+{
+    fn from_f64(x: f64) -> Self;
+}
+
+impl FlowFloat for f64 {
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+}
+
+impl FlowFloat for f32 {
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+}
+
+/// Alternatives sorted in descending order of net flow, where each group of consecutive alternatives
+/// whose net flows are equal up to `FLOW_EPS` (see `flows_equal`) is sorted with tie_order.
+/// Tied alternatives are grouped with the previous one in the ranking, so a group may span more than `FLOW_EPS`.
+fn rank_with_ties<F>(net_flows: &[f64], tie_order: F) -> Vec<usize>
+where
+    F: FnMut(&usize, &usize) -> std::cmp::Ordering,
+{
+    tied_groups(net_flows, FLOW_EPS, tie_order)
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Rank of each alternative in descending order of net flow, from 1, tied alternatives (see `rank_with_ties`)
+/// sharing the average of their ranks
+fn average_ranks(net_flows: &[f64]) -> Vec<f64> {
+    let mut ranks = vec![0.0; net_flows.len()];
+    let mut start = 1;
+    for group in tied_groups(net_flows, FLOW_EPS, |i, j| i.cmp(j)) {
+        let rank = start as f64 + (group.len() - 1) as f64 / 2.0;
+        for &i in &group {
+            ranks[i] = rank;
+        }
+        start += group.len();
+    }
+    ranks
+}
+
+/// Groups of tied alternatives in descending order of net flow, as in `rank_with_ties` with eps instead of `FLOW_EPS`
+fn tied_groups<F>(net_flows: &[f64], eps: f64, mut tie_order: F) -> Vec<Vec<usize>>
+where
+    F: FnMut(&usize, &usize) -> std::cmp::Ordering,
+{
+    let ranked: Vec<usize> = (0..net_flows.len())
+        .sorted_by(|&i, &j| net_flows[j].partial_cmp(&net_flows[i]).unwrap())
+        .collect();
+
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for end in 1..=ranked.len() {
+        if end == ranked.len() || (net_flows[ranked[end - 1]] - net_flows[ranked[end]]).abs() > eps
+        {
+            let mut group = ranked[start..end].to_vec();
+            group.sort_by(&mut tie_order);
+            groups.push(group);
+            start = end;
+        }
+    }
+    groups
+}
+
+/// Check that the weights can be normalized, returning their sum
+fn check_weight_sum(weights: &[f64]) -> Result<f64, PrometheeError> {
+    let sum: f64 = weights.iter().sum();
+    if sum.is_finite() && sum.abs() >= MIN_WEIGHT_SUM {
+        Ok(sum)
+    } else {
+        Err(PrometheeError::DegenerateWeights { sum })
+    }
+}
+
+/// Quantile of sorted values, using linear interpolation between the closest ranks
+fn quantile(sorted_values: &[f64], f: f64) -> f64 {
+    let pos = f * (sorted_values.len() - 1) as f64;
+    let (low, up) = (pos.floor() as usize, pos.ceil() as usize);
+    sorted_values[low] + (pos - low as f64) * (sorted_values[up] - sorted_values[low])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alternatives::{Alternative, AlternativeTable};
+
+    fn init_simple_problem() -> PrometheeProblem {
+        let alt_table = AlternativeTable::new(
+            vec![
+                Alternative::new("A".to_string(), vec![3.0, 1.0]),
+                Alternative::new("B".to_string(), vec![2.0, 4.0]),
+                Alternative::new("C".to_string(), vec![2.0, 3.0]),
+            ]
+            .into(),
+        );
+        // let prob_matrix: Vec<Vec<f64>> = vec![vec![3.0, 2.0, 2.0], vec![1.0, 4.0, 3.0]];
+        let weights: Vec<f64> = vec![3.0, 7.0];
+        let criteria: Vec<GeneralizedCriterion> = vec![
+            GeneralizedCriterion::VShape { p: 3.0 },
+            GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+        ];
+
+        PrometheeProblem::new(alt_table, criteria, weights)
+    }
+
+    /// Problem with n alternatives whose evaluations are pseudo-random values in [offset, offset + scale)
+    fn init_random_problem(
+        n: usize,
+        offset: f64,
+        scale: f64,
+        criteria: Vec<GeneralizedCriterion>,
+    ) -> PrometheeProblem {
+        let mut rng = Lcg(42);
+        let q = criteria.len();
+        let matrix = (0..n)
+            .map(|_| (0..q).map(|_| offset + rng.next_f64() * scale).collect())
+            .collect();
+
+        PrometheeProblem::new(
+            AlternativeTable::from_matrix(matrix),
+            criteria,
+            vec![1.0; q],
+        )
+    }
+
+    /// Unicriterion flows of criterion k computed with the pairwise method
+    fn slow_flows(problem: &PrometheeProblem, k: usize) -> (Vec<f64>, Vec<f64>) {
+        let fks = problem.oriented_criterion(k);
+        let dist_mat: Vec<Vec<f64>> = fks
+            .iter()
+            .map(|&a_i| fks.iter().map(|&a_j| a_i - a_j).collect())
+            .collect();
+        problem.slow_unicriterion_flows(&dist_mat, &problem.generalized_criteria[k])
+    }
+
+    fn assert_close(expected: &[f64], actual: &[f64], tol: f64) {
+        assert_eq!(expected.len(), actual.len());
+        for (i, (e, a)) in expected.iter().zip(actual).enumerate() {
+            assert!((e - a).abs() < tol, "at index {}: {} != {}", i, e, a);
+        }
+    }
+
+    fn round_vec(v: &mut [f64]) -> Vec<f64> {
+        v.iter().map(|fl| (fl * 1000.0).round() / 1000.0).collect()
+    }
+
+    #[test]
+    fn test_solve() {
+        let problem = init_simple_problem();
+
+        let solution = problem.solve();
+        let final_net_flow: Vec<f64> = solution
+            .net_flows()
+            .iter()
+            .map(|fl| (fl * 1000.0).round() / 1000.0)
+            .collect();
+        let real_solution = [-0.425, 0.3, 0.125];
+        let mut equality = real_solution
+            .iter()
+            .enumerate()
+            .map(|(i, &val)| val == final_net_flow[i]);
+
+        assert!(equality.all(|x| x))
+    }
+
+    #[test]
+    fn test_solve_2() {
+        let alt_table = AlternativeTable::new(
+            vec![
+                Alternative::new("A".to_string(), vec![3.0, 1.0]),
+                Alternative::new("B".to_string(), vec![2.0, 4.0]),
+                Alternative::new("C".to_string(), vec![0.0, 5.0]),
+            ]
+            .into(),
+        );
+        // let prob_matrix: Vec<Vec<f64>> = vec![vec![3.0, 2.0, 0.0], vec![1.0, 4.0, 5.0]];
+        let weights: Vec<f64> = vec![1.0, 1.0];
+        let criteria: Vec<GeneralizedCriterion> = vec![
+            GeneralizedCriterion::VShape { p: 2.0 },
+            GeneralizedCriterion::VShape { p: 3.0 },
+        ];
+
+        let problem = PrometheeProblem::new(alt_table, criteria, weights);
+
+        let solution = problem.solve();
+        let final_net_flow: Vec<f64> = solution
+            .net_flows()
+            .iter()
+            .map(|fl| (fl * 1000.0).round() / 1000.0)
+            .collect();
+        let real_solution = [-0.125, 0.292, -0.167];
+        // println!("{:#?}", final_net_flow);
+        let mut equality = real_solution
+            .iter()
+            .enumerate()
+            .map(|(i, &val)| val == final_net_flow[i]);
+
+        assert!(equality.all(|x| x))
+    }
+
+    #[test]
+    fn solve_fast_and_slow_equivalent() {
+        let problem = init_simple_problem();
+
+        for k in 0..problem.q {
+            let generalized_criterion = &problem.generalized_criteria[k];
+            let dist_mat: Vec<Vec<f64>> = problem
+                .alt_table
+                .criterion(k)
+                .unwrap()
+                .iter()
+                .map(|&a_i| {
+                    problem
+                        .alt_table
+                        .criterion(k)
+                        .unwrap()
+                        .iter()
+                        .map(move |&a_j| a_i - a_j.to_owned())
+                        .collect()
+                })
+                .collect();
+            let (mut slow_pos_flow, mut slow_neg_flow) =
+                problem.slow_unicriterion_flows(&dist_mat, generalized_criterion);
+            slow_pos_flow = round_vec(&mut slow_pos_flow);
+            slow_neg_flow = round_vec(&mut slow_neg_flow);
+
+            let (mut fast_pos_flow, mut fast_neg_flow) =
+                problem.fast_unicriterion_flows(k).unwrap();
+            fast_pos_flow = round_vec(&mut fast_pos_flow);
+            fast_neg_flow = round_vec(&mut fast_neg_flow);
+
+            println!("{:#?}", slow_pos_flow);
+            println!("{:#?}", slow_neg_flow);
+            println!();
+
+            println!("{:#?}", fast_pos_flow);
+            println!("{:#?}", fast_neg_flow);
+
+            assert!(slow_pos_flow
+                .iter()
+                .enumerate()
+                .all(|(i, &val)| val == fast_pos_flow[i]));
+
+            assert!(slow_neg_flow
+                .iter()
+                .enumerate()
+                .all(|(i, &val)| val == fast_neg_flow[i]));
+        }
+    }
+
+    #[test]
+    fn solve_verbose_matches_solve() {
+        let problem = init_simple_problem();
+
+        let solution = problem.solve();
+        let (verbose_solution, preference_matrix) = problem.solve_verbose();
+
+        let n = problem.n();
+        for i in 0..n {
+            assert!((solution.positive_flows[i] - verbose_solution.positive_flows[i]).abs() < 1e-9);
+            assert!((solution.negative_flows[i] - verbose_solution.negative_flows[i]).abs() < 1e-9);
+
+            // Flows are the normalised row and column sums of the preference matrix
+            let row_sum: f64 = preference_matrix[i].iter().sum();
+            let col_sum: f64 = preference_matrix.iter().map(|row| row[i]).sum();
+            assert!((row_sum / (n as f64 - 1.0) - solution.positive_flows[i]).abs() < 1e-9);
+            assert!((col_sum / (n as f64 - 1.0) - solution.negative_flows[i]).abs() < 1e-9);
+            assert_eq!(preference_matrix[i][i], 0.0);
+        }
+    }
+
+    #[test]
+    fn promethee1_incomparabilities() {
+        // A and B are incomparable, B is preferred to C and C is indifferent to D
+        let result = Promethee1Result::new(vec![0.5, 0.6, 0.4, 0.4], vec![0.1, 0.3, 0.4, 0.4]);
+
+        assert_eq!(result.relation(1, 2), Some(PartialPreference::Prefers));
+        assert_eq!(result.relation(2, 1), Some(PartialPreference::PreferredBy));
+        assert_eq!(result.relation(2, 3), Some(PartialPreference::Indifferent));
+        assert_eq!(result.relation(0, 1), Some(PartialPreference::Incomparable));
+        assert_eq!(result.relation(0, 0), Some(PartialPreference::Indifferent));
+
+        assert_eq!(result.incomparable_pairs(), vec![(0, 1)]);
+        assert_eq!(result.incomparability_count(), 1);
+    }
+
+    #[test]
+    fn promethee1_outrank_counts() {
+        let result = Promethee1Result::new(vec![0.5, 0.6, 0.4, 0.4], vec![0.1, 0.3, 0.4, 0.4]);
+        // A and B are preferred to C and D, which are indifferent
+        assert_eq!(result.outrank_counts(), vec![2, 2, 0, 0]);
+
+        let result = init_simple_problem().solve_partial();
+        let prefers = result
+            .preferences()
+            .iter()
+            .flatten()
+            .filter(|&&relation| relation == PartialPreference::Prefers)
+            .count();
+        assert_eq!(result.outrank_counts().iter().sum::<usize>(), prefers);
+    }
+
+    #[test]
+    fn promethee1_from_problem() {
+        let problem = init_simple_problem();
+        let result = problem.solve_partial();
+
+        assert_eq!(result.n(), problem.n());
+        for (a, b) in result.incomparable_pairs() {
+            assert_eq!(result.relation(b, a), Some(PartialPreference::Incomparable));
+        }
+    }
+
+    #[test]
+    fn auto_threshold_ignores_missing_values() {
+        let values = [1.0, f64::NAN, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(
+            PrometheeProblem::auto_threshold(&values),
+            PrometheeProblem::auto_threshold(&[1.0, 2.0, 3.0, 4.0, 5.0])
+        );
+        assert_eq!(PrometheeProblem::auto_threshold(&[f64::NAN]), 0.0);
+    }
+
+    #[test]
+    fn suggest_criteria_binary_and_continuous() {
+        let table = AlternativeTable::from_matrix(vec![
+            vec![0.0, 1.0],
+            vec![1.0, 2.0],
+            vec![1.0, 3.0],
+            vec![0.0, 4.0],
+            vec![1.0, 5.0],
+        ]);
+
+        let suggestions = PrometheeProblem::suggest_criteria(&table);
+
+        assert_eq!(suggestions[0], GeneralizedCriterion::Usual);
+        assert_eq!(suggestions[1], GeneralizedCriterion::VShape { p: 2.0 });
+    }
+
+    #[test]
+    fn solve_with_weights_leaves_problem_untouched() {
+        let problem = init_simple_problem();
+        let weights_before: Vec<f64> = (0..problem.q()).map(|k| *problem.w(k).unwrap()).collect();
+
+        // Same weights as the problem, up to normalization
+        let same = problem.solve_with_weights(&[6.0, 14.0]);
+        let other = problem.solve_with_weights(&[1.0, 0.0]);
+        problem.solve_with_weights(&[0.5, 0.5]);
+
+        let weights_after: Vec<f64> = (0..problem.q()).map(|k| *problem.w(k).unwrap()).collect();
+        assert_eq!(weights_before, weights_after);
+
+        problem
+            .solve()
+            .net_flows()
+            .iter()
+            .zip(same.net_flows())
+            .for_each(|(a, b)| assert!((a - b).abs() < 1e-9));
+        assert_eq!(other.net_flows(), other.unicriterion_net_flows(0));
+    }
+
+    #[test]
+    fn aggregate_unicriterion_flows() {
+        let problem = init_simple_problem();
+        let unicriterion_flows = problem.compute_unicriterion_flows();
+
+        for weights in [vec![3.0, 7.0], vec![1.0, 1.0], vec![0.0, 2.0]] {
+            let aggregated = unicriterion_flows.aggregate(&weights);
+            let solved = problem.solve_with_weights(&weights);
+            aggregated
+                .net_flows()
+                .iter()
+                .zip(solved.net_flows())
+                .for_each(|(a, b)| assert!((a - b).abs() < 1e-9));
+        }
+    }
+
+    #[test]
+    fn thresholds_of_each_criterion() {
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![1.0, 2.0, 3.0, 4.0], vec![2.0, 1.0, 0.0, 3.0]]),
+            vec![
+                GeneralizedCriterion::Usual,
+                GeneralizedCriterion::VShape { p: 2.0 },
+                GeneralizedCriterion::Linear { q: 0.5, p: 1.5 },
+                GeneralizedCriterion::UShape { p: 1.0 },
+            ],
+            vec![1.0; 4],
+        );
+
+        assert_eq!(problem.thresholds(0), Some((None, None)));
+        assert_eq!(problem.thresholds(1), Some((None, Some(2.0))));
+        assert_eq!(problem.thresholds(2), Some((Some(0.5), Some(1.5))));
+        assert_eq!(problem.thresholds(3), Some((Some(1.0), None)));
+        assert_eq!(problem.thresholds(4), None);
+    }
+
+    #[test]
+    fn verify_cache_detects_corruption() {
+        let mut problem = init_simple_problem();
+        assert!(problem.debug_verify_cache());
+
+        problem.shift_eval(1, 0, 10.0);
+        assert!(problem.debug_verify_cache());
+
+        // Reverse the argsort of the first criterion
+        if let Some(argsorted_fks) = problem.argsorted_eval_matrix[0].as_mut() {
+            argsorted_fks.reverse();
+        }
+        assert!(!problem.debug_verify_cache());
+
+        problem.argsorted_eval_matrix[0] = None;
+        assert!(!problem.debug_verify_cache());
+    }
+
+    #[test]
+    fn ranking_table_round_trip() {
+        let problem = init_simple_problem();
+        let result = problem.solve();
+        let ranking = problem.ranking_table(&result);
+
+        assert_eq!(ranking.len(), 3);
+        assert_eq!(ranking.indices(), result.ranked_alts());
+        assert_eq!(ranking.position(0).unwrap().name, "B");
+        assert_eq!(
+            ranking.position(0).unwrap().net_flow,
+            result.net_flow(1).unwrap()
+        );
+
+        let json = serde_json::to_string(&ranking).unwrap();
+        let deserialized: Ranking = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, ranking);
+    }
+
+    #[test]
+    fn net_flow_gradient_matches_finite_differences() {
+        let problem = init_simple_problem();
+        let weights = [0.3, 0.7];
+        let result = problem.solve_with_weights(&weights);
+        let gradient = result.net_flow_gradient();
+        let h = 1e-6;
+
+        for k in 0..problem.q() {
+            let mut shifted_weights = weights;
+            shifted_weights[k] += h;
+            let shifted = problem.solve_with_weights(&shifted_weights);
+
+            for (i, gradient_i) in gradient.iter().enumerate() {
+                let finite_diff = (shifted.net_flow(i).unwrap() - result.net_flow(i).unwrap()) / h;
+                assert!((finite_diff - gradient_i[k]).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn preserve_signs_matches_negated_evaluations() {
+        let alternatives: Box<[Alternative]> = vec![
+            Alternative::new("A".to_string(), vec![3.0, 1.0, 5.0]),
+            Alternative::new("B".to_string(), vec![2.0, 4.0, 1.0]),
+            Alternative::new("C".to_string(), vec![2.0, 3.0, 2.5]),
+            Alternative::new("D".to_string(), vec![0.5, 3.5, 4.0]),
+        ]
+        .into();
+        let criteria = || {
+            vec![
+                GeneralizedCriterion::VShape { p: 3.0 },
+                GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+                GeneralizedCriterion::Usual,
+            ]
+        };
+        let weights = vec![3.0, 7.0, 2.0];
+
+        let mut negated_table = AlternativeTable::new(alternatives.clone());
+        negated_table.swap_criteria_direction(1);
+        negated_table.swap_criteria_direction(2);
+        let negated = PrometheeProblem::new(negated_table, criteria(), weights.clone());
+
+        let preserved_table = AlternativeTable::new(alternatives).with_criteria_directions(vec![
+            OptimizationDirection::Max,
+            OptimizationDirection::Min,
+            OptimizationDirection::Min,
+        ]);
+        let preserved = PrometheeProblem::new_preserve_signs(preserved_table, criteria(), weights);
+
+        assert_eq!(*preserved.perf(1, 0).unwrap(), 1.0);
+        assert_eq!(*negated.perf(1, 0).unwrap(), -1.0);
+        assert_eq!(preserved.sorted_evals(1), vec![1.0, 3.0, 3.5, 4.0]);
+        assert!(preserved.debug_verify_cache());
+
+        negated
+            .solve()
+            .net_flows()
+            .iter()
+            .zip(preserved.solve().net_flows())
+            .for_each(|(a, b)| assert!((a - b).abs() < 1e-9));
+    }
+
+    #[test]
+    fn fast_and_slow_equivalent_large_scale() {
+        // Evaluations in the millions with a narrow spread, so that the window sums are large
+        let problem = init_random_problem(
+            2000,
+            10e6,
+            10.0,
+            vec![
+                GeneralizedCriterion::Linear { q: 0.1, p: 2.0 },
+                GeneralizedCriterion::VShape { p: 5.0 },
+            ],
+        );
+
+        for k in 0..problem.q() {
+            let (slow_pos_flow, slow_neg_flow) = slow_flows(&problem, k);
+            let (fast_pos_flow, fast_neg_flow) = problem.fast_unicriterion_flows(k).unwrap();
+
+            assert_close(&slow_pos_flow, &fast_pos_flow, 1e-9);
+            assert_close(&slow_neg_flow, &fast_neg_flow, 1e-9);
+        }
+    }
+
+    #[test]
+    fn sorted_with_indices_cached_and_on_demand() {
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![3.0, 1.0], vec![1.0, 4.0], vec![2.0, 3.0]]),
+            vec![
+                GeneralizedCriterion::VShape { p: 3.0 },
+                GeneralizedCriterion::Usual,
+            ],
+            vec![1.0, 1.0],
+        );
+
+        assert_eq!(
+            problem.sorted_with_indices(0),
+            vec![(1, 1.0), (2, 2.0), (0, 3.0)]
+        );
+        assert_eq!(
+            problem.sorted_with_indices(1),
+            vec![(0, 1.0), (2, 3.0), (1, 4.0)]
+        );
+        assert_eq!(problem.sorted_evals(1), vec![1.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn ranked_alts_tiebreak_uses_secondary_key() {
+        let result = Promethee2Result {
+            positive_flows: vec![0.5, 0.4, 0.6, 0.2],
+            unicrit_positive_flows: vec![vec![0.5, 0.4, 0.6, 0.2]],
+            negative_flows: vec![0.2, 0.1, 0.3, 0.0],
+            unicrit_negative_flows: vec![vec![0.2, 0.1, 0.3, 0.0]],
+        };
+        // Alternatives 0, 1 and 2 are tied with a net flow of 0.3
+        let secondary = [1.0, 3.0, 2.0, 10.0];
+
+        assert_eq!(result.ranked_alts_tiebreak(&secondary), vec![1, 2, 0, 3]);
+        // Ties remaining after the secondary key are broken by ascending index
+        assert_eq!(result.ranked_alts_tiebreak(&[0.0; 4]), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn entropy_weights_constant_criterion() {
+        let table = AlternativeTable::from_matrix(vec![
+            vec![3.0, 5.0, 10.0],
+            vec![1.0, 5.0, 11.0],
+            vec![2.0, 5.0, 30.0],
+            vec![4.0, 5.0, 12.0],
+        ]);
+
+        let weights = PrometheeProblem::entropy_weights(&table);
+
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(weights[1].abs() < 1e-9);
+        // The first criterion is spread more evenly than the third one, which has an outlier
+        assert!(weights[0] < weights[2]);
+    }
+
+    #[test]
+    fn condorcet_winner_and_cycle() {
+        let problem = init_simple_problem();
+        let result = problem.solve();
+        let (_, pi) = problem.solve_verbose();
+
+        assert_eq!(problem.preference_matrix(), pi);
+        // B has the best net flow but is indifferent to C on both criteria
+        assert_eq!(result.ranked_alts()[0], 1);
+        assert_eq!(pi[1][2], pi[2][1]);
+        assert_eq!(result.condorcet_winner(&problem), None);
+
+        let with_winner = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![
+                vec![1.0, 1.0, 1.0],
+                vec![2.0, 2.0, 0.0],
+                vec![0.0, 3.0, 2.0],
+            ]),
+            vec![GeneralizedCriterion::Usual; 3],
+            vec![1.0; 3],
+        );
+        assert_eq!(with_winner.solve().condorcet_winner(&with_winner), Some(2));
+
+        // Condorcet paradox: each alternative is beaten by another one on two criteria out of three
+        let cycle = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![
+                vec![1.0, 2.0, 3.0],
+                vec![2.0, 3.0, 1.0],
+                vec![3.0, 1.0, 2.0],
+            ]),
+            vec![GeneralizedCriterion::Usual; 3],
+            vec![1.0; 3],
+        );
+        assert_eq!(cycle.solve().condorcet_winner(&cycle), None);
+    }
+
+    #[test]
+    fn pairwise_preference_matches_matrix() {
+        let problem = init_random_problem(
+            15,
+            0.0,
+            10.0,
+            vec![
+                GeneralizedCriterion::VShape { p: 4.0 },
+                GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+                GeneralizedCriterion::Usual,
+            ],
+        );
+        let pi = problem.preference_matrix();
+
+        for (a, row) in pi.iter().enumerate() {
+            for (b, pi_ab) in row.iter().enumerate() {
+                assert!((problem.pairwise_preference(a, b) - pi_ab).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn promethee3_intervals_and_order() {
+        let result = init_simple_problem().solve();
+
+        let intervals = result.promethee3_intervals(0.5);
+        let expected = [
+            (-0.425, 0.5 * 13.0 / 24.0),
+            (0.3, 0.5 / 3.0),
+            (0.125, 0.5 * 5.0 / 24.0),
+        ];
+        for ((low, up), (phi, half_width)) in intervals.iter().zip(expected) {
+            assert!((low - (phi - half_width)).abs() < 1e-9);
+            assert!((up - (phi + half_width)).abs() < 1e-9);
+        }
+
+        // B and C overlap, both are above A
+        assert_eq!(result.promethee3_order(0.5), vec![(1, 0), (2, 0)]);
+        // Without spread the order is the complete Promethee II ranking
+        assert_eq!(result.promethee3_order(0.0), vec![(1, 0), (1, 2), (2, 0)]);
+    }
+
+    #[test]
+    fn try_ranked_alts_reports_nan() {
+        let mut result = init_simple_problem().solve();
+        assert_eq!(result.try_ranked_alts(), Ok(result.ranked_alts()));
+
+        result.unicrit_negative_flows[1][2] = f64::NAN;
+        result.negative_flows[2] = f64::NAN;
+        let err = result.try_ranked_alts().unwrap_err();
+        assert_eq!(
+            err,
+            RankingError::NonFiniteFlow {
+                alternative: 2,
+                criterion: Some(1)
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Non finite net flow for alternative 2, caused by criterion 1"
+        );
+    }
+
+    #[test]
+    fn pairwise_breakdown_sums_to_preference() {
+        let problem = init_simple_problem();
+
+        // Weights 0.3 and 0.7, P_0 is a V-Shape with p = 3 and P_1 a Linear with q = 1 and p = 3:
+        // B is preferred to A only because of the second criterion
+        assert_close(&problem.pairwise_breakdown(1, 0), &[0.0, 0.7], 1e-12);
+        assert_close(&problem.pairwise_breakdown(0, 1), &[0.1, 0.0], 1e-12);
+        assert_close(&problem.pairwise_breakdown(2, 0), &[0.0, 0.35], 1e-12);
+        assert_close(&problem.pairwise_breakdown(1, 2), &[0.0, 0.0], 1e-12);
+
+        for (a, row) in problem.preference_matrix().iter().enumerate() {
+            for (b, pi_ab) in row.iter().enumerate().filter(|&(b, _)| b != a) {
+                let total: f64 = problem.pairwise_breakdown(a, b).iter().sum();
+                assert!((total - pi_ab).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn slow_flows_exclude_diagonal() {
+        let problem = init_simple_problem();
+        let criterion = GeneralizedCriterion::Linear { q: 0.0, p: 2.0 };
+        let mut dist_mat = vec![
+            vec![0.0, 1.0, 2.0],
+            vec![-1.0, 0.0, 1.0],
+            vec![-2.0, -1.0, 0.0],
+        ];
+        let expected = problem.slow_unicriterion_flows(&dist_mat, &criterion);
+
+        // Non zero self comparisons, as a criterion with P(0) > 0 would give, must not change the flows
+        for (i, row) in dist_mat.iter_mut().enumerate() {
+            row[i] = 1.5;
+        }
+        assert_eq!(
+            problem.slow_unicriterion_flows(&dist_mat, &criterion),
+            expected
+        );
+        assert_eq!(expected.0, vec![0.75, 0.25, 0.0]);
+    }
+
+    #[test]
+    fn solve_excluding_matches_reduced_problem() {
+        let criteria = vec![
+            GeneralizedCriterion::VShape { p: 4.0 },
+            GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+            GeneralizedCriterion::Usual,
+            GeneralizedCriterion::AsymmetricLinear {
+                q_pos: 0.5,
+                p_pos: 3.0,
+                q_neg: 0.0,
+                p_neg: 1.5,
+            },
+        ];
+        let problem = init_random_problem(12, 0.0, 10.0, criteria.clone());
+        let exclude = [3, 7];
+
+        let retained = problem.retained_indices(&exclude);
+        assert_eq!(retained, vec![0, 1, 2, 4, 5, 6, 8, 9, 10, 11]);
+
+        let reduced = PrometheeProblem::new(
+            AlternativeTable::from_matrix(
+                retained
+                    .iter()
+                    .map(|&ai| problem.alt_table.alternative(ai).unwrap().perfs().to_vec())
+                    .collect(),
+            ),
+            criteria,
+            vec![1.0; 4],
+        );
+
+        assert_close(
+            &reduced.solve().net_flows(),
+            &problem.solve_excluding(&exclude).net_flows(),
+            1e-12,
+        );
+        let (solution, excluding_none) = (problem.solve(), problem.solve_excluding(&[]));
+        assert_close(
+            &solution.negative_flows,
+            &excluding_none.negative_flows,
+            1e-12,
+        );
+        assert_close(&solution.net_flows(), &excluding_none.net_flows(), 1e-12);
+
+        // The normalization of the problem applies to the retained alternatives
+        let problem = problem.with_flow_normalization(FlowNormalization::N);
+        assert_close(
+            &reduced
+                .with_flow_normalization(FlowNormalization::N)
+                .solve()
+                .net_flows(),
+            &problem.solve_excluding(&exclude).net_flows(),
+            1e-12,
+        );
+
+        // Comparisons with a missing evaluation are left out of the normalization
+        let problem = builder::PrometheeProblemBuilder::new()
+            .criterion(
+                "c0",
+                OptimizationDirection::Max,
+                GeneralizedCriterion::VShape { p: 3.0 },
+                3.0,
+            )
+            .and_then(|b| {
+                b.criterion(
+                    "c1",
+                    OptimizationDirection::Max,
+                    GeneralizedCriterion::Usual,
+                    7.0,
+                )
+            })
+            .and_then(|b| b.alternative("A", vec![3.0, 1.0]))
+            .and_then(|b| b.alternative("B", vec![2.0, f64::NAN]))
+            .and_then(|b| b.alternative("C", vec![2.0, 3.0]))
+            .and_then(|b| b.alternative("D", vec![f64::NAN, 2.0]))
+            .unwrap()
+            .allow_missing(true)
+            .build()
+            .unwrap();
+        let (solution, excluding_none) = (problem.solve(), problem.solve_excluding(&[]));
+        assert_close(
+            &solution.positive_flows,
+            &excluding_none.positive_flows,
+            1e-12,
+        );
+        assert_close(
+            &solution.negative_flows,
+            &excluding_none.negative_flows,
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn promethee1_serde_round_trip() {
+        let result = init_simple_problem().solve_partial();
+        let json = serde_json::to_string(&result).unwrap();
+
+        // The relation of an alternative with itself is encoded as indifference
+        assert!(json.contains("\"preferences\":[[1,"));
+
+        let restored: Promethee1Result = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.positive_flows, result.positive_flows);
+        assert_eq!(restored.negative_flows, result.negative_flows);
+        assert_eq!(restored.preferences(), result.preferences());
+
+        let invalid = json.replacen("[[1,", "[[7,", 1);
+        assert!(serde_json::from_str::<Promethee1Result>(&invalid).is_err());
+    }
+
+    #[test]
+    fn flat_criterion_has_zero_flows() {
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![1.0, 4.0], vec![2.0, 4.0], vec![3.0, 4.0]]),
+            vec![GeneralizedCriterion::VShape { p: 2.0 }; 2],
+            vec![1.0, 1.0],
+        );
+
+        assert_eq!(problem.warn_flat_criteria(), vec![1]);
+        assert_eq!(problem.solve().unicriterion_net_flows(1), vec![0.0; 3]);
+    }
+
+    #[test]
+    fn shift_eval_keeps_argsort_sorted() {
+        let mut problem = init_random_problem(
+            50,
+            0.0,
+            10.0,
+            vec![
+                GeneralizedCriterion::VShape { p: 4.0 },
+                GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+                GeneralizedCriterion::Usual,
+            ],
+        );
+
+        for (step, shift) in [3.5, -7.0, 0.0, 12.0, -0.25].into_iter().enumerate() {
+            let i = (7 * step + 3) % problem.n();
+            for k in 0..problem.q() {
+                problem.shift_eval(k, i, shift);
+            }
+            assert!(problem.debug_verify_cache());
+
+            let incremental = problem.argsorted_eval_matrix[1].clone().unwrap();
+            problem.argsort_evals(1);
+            let full = problem.argsorted_eval_matrix[1].clone().unwrap();
+            let values = |argsort: &[usize]| -> Vec<f64> {
+                argsort
+                    .iter()
+                    .map(|&alt| *problem.perf(1, alt).unwrap())
+                    .collect()
+            };
+            assert_eq!(values(&incremental), values(&full));
+        }
+        assert!(problem.argsorted_eval_matrix[2].is_none());
+    }
+
+    #[test]
+    fn decisive_criterion_simple_problem() {
+        let problem = init_simple_problem();
+
+        // B beats A by 0.7 on the second criterion and loses 0.1 on the first one
+        assert_eq!(problem.decisive_criterion(1, 0), Some(1));
+        assert_eq!(problem.decisive_criterion(0, 1), None);
+        assert_eq!(problem.decisive_criterion(2, 0), Some(1));
+        // B and C are indifferent
+        assert_eq!(problem.decisive_criterion(1, 2), None);
+        assert_eq!(problem.decisive_criterion(2, 1), None);
+    }
+
+    #[test]
+    fn try_solve_detects_corrupt_problem() {
+        let problem = init_simple_problem();
+        assert!(problem.validate().is_ok());
+
+        let mut json = serde_json::to_value(&problem).unwrap();
+        json["weights"].as_array_mut().unwrap().pop();
+        let corrupt: PrometheeProblem = serde_json::from_value(json).unwrap();
+
+        let err = corrupt.try_solve().unwrap_err();
+        assert_eq!(
+            err,
+            PrometheeError::DimensionMismatch {
+                field: "weights".to_string(),
+                given: 1,
+                expected: 2
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Wrong number of weights, 1 given, 2 expected"
+        );
+    }
+
+    #[test]
+    fn best_weights_for_each_alternative() {
+        let problem = init_simple_problem();
+
+        // A wins when the first criterion has enough weight
+        let weights = problem.best_weights_for(0).unwrap();
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(problem.solve_with_weights(&weights).ranked_alts()[0], 0);
+
+        let weights = problem.best_weights_for(1).unwrap();
+        assert_eq!(problem.solve_with_weights(&weights).ranked_alts()[0], 1);
+
+        // C is tied with B on the first criterion and worse on the second one
+        assert_eq!(problem.best_weights_for(2), None);
+    }
+
+    #[test]
+    fn solve_with_progress_reports_each_criterion() {
+        let problem = init_simple_problem();
+        let mut calls = Vec::new();
+
+        let result = problem.solve_with_progress(|done, total| calls.push((done, total)));
+
+        assert_eq!(calls, vec![(1, 2), (2, 2)]);
+        assert_eq!(result.net_flows(), problem.solve().net_flows());
+    }
+
+    #[test]
+    fn normalized_problem_has_same_flows() {
+        let problem = init_random_problem(
+            30,
+            100.0,
+            50.0,
+            vec![
+                GeneralizedCriterion::VShape { p: 20.0 },
+                GeneralizedCriterion::Linear { q: 5.0, p: 15.0 },
+                GeneralizedCriterion::UShape { p: 10.0 },
+                GeneralizedCriterion::Usual,
+            ],
+        );
+        let normalized = problem.normalized();
+
+        for k in 0..normalized.q() {
+            let column = normalized.alt_table.criterion(k).unwrap();
+            assert!(column.iter().all(|&f| (0.0..=1.0).contains(&f)));
+        }
+        assert_close(
+            &problem.solve().net_flows(),
+            &normalized.solve().net_flows(),
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn try_new_rejects_zero_weights() {
+        let table = AlternativeTable::from_matrix(vec![vec![1.0, 2.0], vec![2.0, 1.0]]);
+        let criteria = vec![GeneralizedCriterion::Usual; 2];
+
+        let err =
+            PrometheeProblem::try_new(table.clone(), criteria.clone(), vec![0.0, 0.0]).unwrap_err();
+        assert_eq!(err, PrometheeError::DegenerateWeights { sum: 0.0 });
+
+        let err =
+            PrometheeProblem::try_new(table.clone(), criteria.clone(), vec![1.0]).unwrap_err();
+        assert!(matches!(
+            err,
+            PrometheeError::DimensionMismatch { given: 1, .. }
+        ));
+
+        assert!(PrometheeProblem::try_new(table, criteria, vec![0.0, 2.0]).is_ok());
+    }
+
+    #[test]
+    fn rank_gaps_simple_problem() {
+        let result = init_simple_problem().solve();
+
+        // Net flows are 0.3 for B, 0.125 for C and -0.425 for A
+        assert_close(&[0.175, 0.55], &result.rank_gaps(), 1e-12);
+        assert!((result.winner_margin() - 0.175).abs() < 1e-12);
+    }
+
+    #[test]
+    fn flows_against_two_profiles() {
+        let problem = init_simple_problem();
+        let profiles = AlternativeTable::from_matrix(vec![vec![2.0, 2.0], vec![3.0, 3.0]]);
+
+        let (positive_flows, negative_flows) = problem.flows_against(&profiles);
+
+        assert_close(&[0.05, 0.175, 0.0], &positive_flows, 1e-12);
+        assert_close(&[0.175, 0.05, 0.05], &negative_flows, 1e-12);
+    }
+
+    #[test]
+    fn weighted_unicriterion_net_flows_sum_to_net_flows() {
+        let problem = init_simple_problem();
+        let result = problem.solve();
+
+        let matrix = result.weighted_unicriterion_net_flows(&[3.0, 7.0]);
+        assert_eq!(matrix.len(), problem.n());
+
+        let row_sums: Vec<f64> = matrix.iter().map(|row| row.iter().sum()).collect();
+        assert_close(&result.net_flows(), &row_sums, 1e-12);
+    }
+
+    #[test]
+    fn net_preference_matrix_rows_give_net_flows() {
+        let problem = init_random_problem(
+            20,
+            0.0,
+            10.0,
+            vec![
+                GeneralizedCriterion::VShape { p: 4.0 },
+                GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+                GeneralizedCriterion::Usual,
+            ],
+        );
+        let m = problem.net_preference_matrix();
+
+        for (a, row) in m.iter().enumerate() {
+            assert_eq!(row[a], 0.0);
+            for (b, m_ab) in row.iter().enumerate() {
+                assert_eq!(*m_ab, -m[b][a]);
+            }
+        }
+
+        let row_flows: Vec<f64> = m
+            .iter()
+            .map(|row| row.iter().sum::<f64>() / (problem.n() as f64 - 1.0))
+            .collect();
+        assert_close(&problem.solve().net_flows(), &row_flows, 1e-12);
+    }
+
+    #[test]
+    fn flow_normalization_modes() {
+        let default = init_simple_problem().solve();
+        let n_minus_one = init_simple_problem()
+            .with_flow_normalization(FlowNormalization::NMinusOne)
+            .solve();
+        let n = init_simple_problem().with_flow_normalization(FlowNormalization::N);
+        let (verbose, _) = n.solve_verbose();
+        let n = n.solve();
+
+        assert_eq!(default.net_flows(), n_minus_one.net_flows());
+        assert_close(&[-0.425, 0.3, 0.125], &default.net_flows(), 1e-12);
+
+        // Dividing by 3 instead of 2 scales every flow by 2/3, in both methods
+        let scaled: Vec<f64> = default.net_flows().iter().map(|f| f * 2.0 / 3.0).collect();
+        assert_close(&scaled, &n.net_flows(), 1e-12);
+        assert_close(&scaled, &verbose.net_flows(), 1e-12);
+        let scaled_pos: Vec<f64> = default
+            .positive_flows
+            .iter()
+            .map(|f| f * 2.0 / 3.0)
+            .collect();
+        assert_close(&scaled_pos, &n.positive_flows, 1e-12);
+    }
+
+    #[test]
+    fn validation_rejects_zero_p() {
+        let table = AlternativeTable::from_matrix(vec![vec![1.0, 2.0], vec![2.0, 1.0]]);
+        let criteria = vec![
+            GeneralizedCriterion::Usual,
+            GeneralizedCriterion::VShape { p: 0.0 },
+        ];
+
+        let err = PrometheeProblem::try_new(table, criteria, vec![1.0, 1.0]).unwrap_err();
+        assert!(matches!(
+            err,
+            PrometheeError::InvalidThresholds { criterion: 1, .. }
+        ));
+
+        let mut json = serde_json::to_value(init_simple_problem()).unwrap();
+        json["generalized_criteria"][0] = serde_json::json!({ "VShape": { "p": 0.0 } });
+        let corrupt: PrometheeProblem = serde_json::from_value(json).unwrap();
+        assert!(matches!(
+            corrupt.validate(),
+            Err(PrometheeError::InvalidThresholds { criterion: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn validation_rejects_missing_directions_and_names() {
+        for field in ["criteria_direction", "criteria_names"] {
+            let mut json = serde_json::to_value(init_simple_problem()).unwrap();
+            json["alt_table"][field].as_array_mut().unwrap().pop();
+            let corrupt: PrometheeProblem = serde_json::from_value(json).unwrap();
+            assert!(matches!(
+                corrupt.validate(),
+                Err(PrometheeError::DimensionMismatch {
+                    given: 1,
+                    expected: 2,
+                    ..
+                })
+            ));
+        }
+        assert!(init_simple_problem().validate().is_ok());
+    }
+
+    #[test]
+    fn solve_batch_matches_solve_with_weights() {
+        let problem = init_simple_problem();
+        let weight_sets = vec![vec![3.0, 7.0], vec![1.0, 0.0], vec![0.2, 0.8]];
+
+        let results = problem.solve_batch(&weight_sets);
+
+        assert_eq!(results.len(), 3);
+        for (result, weights) in results.iter().zip(&weight_sets) {
+            assert_close(
+                &problem.solve_with_weights(weights).net_flows(),
+                &result.net_flows(),
+                1e-12,
+            );
+        }
+    }
+
+    #[test]
+    fn full_ranking_with_tie() {
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![
+                vec![1.0, 1.0],
+                vec![3.0, 2.0],
+                vec![2.0, 3.0],
+                vec![0.0, 0.0],
+            ]),
+            vec![GeneralizedCriterion::Usual; 2],
+            vec![1.0, 1.0],
+        );
+        let result = problem.solve();
+
+        let rows = problem.full_ranking(&result);
+
+        // a_2 and a_3 are tied, each one is better on one criterion
+        assert_eq!(
+            rows.iter().map(|row| row.rank).collect::<Vec<_>>(),
+            vec![1, 1, 3, 4]
+        );
+        assert_eq!(rows[2].name, "a_1");
+        assert_eq!(rows[3].index, 3);
+        assert!(rows.windows(2).all(|w| w[0].net_flow >= w[1].net_flow));
+        for row in &rows {
+            assert_eq!(row.positive_flow - row.negative_flow, row.net_flow);
+        }
+    }
+
+    #[test]
+    fn dominated_alternative_never_wins() {
+        // C is dominated by B
+        let problem = init_simple_problem();
+
+        assert_eq!(problem.never_winner(500, 3), vec![2]);
+        assert_eq!(problem.sometimes_winner(500, 3), vec![0, 1]);
+    }
+
+    #[test]
+    fn flat_criterion_does_not_discriminate() {
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![1.0, 4.0], vec![2.0, 4.0], vec![3.0, 4.0]]),
+            vec![GeneralizedCriterion::VShape { p: 2.0 }; 2],
+            vec![1.0, 1.0],
+        );
+
+        let discrimination = problem.solve().criterion_discrimination();
+
+        assert!(discrimination[0] > 0.0);
+        assert_eq!(discrimination[1], 0.0);
+    }
+
+    #[test]
+    fn positive_and_negative_only_match_solve() {
+        let problem = init_random_problem(
+            40,
+            0.0,
+            10.0,
+            vec![
+                GeneralizedCriterion::VShape { p: 4.0 },
+                GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+                GeneralizedCriterion::UShape { p: 2.0 },
+                GeneralizedCriterion::Usual,
+            ],
+        );
+        let result = problem.solve();
+
+        assert_close(
+            &result.positive_flows,
+            &problem.solve_positive_only(),
+            1e-12,
+        );
+        assert_close(
+            &result.negative_flows,
+            &problem.solve_negative_only(),
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn min_weight_change_to_swap_simple_problem() {
+        let problem = init_simple_problem();
+
+        // Unicriterion net flow differences between A and B are 0.5 and -1.25, they tie for w = (5/7, 2/7)
+        let (change, weights) = problem.min_weight_change_to_swap(0, 1).unwrap();
+        assert_close(&[5.0 / 7.0, 2.0 / 7.0], &weights, 1e-9);
+        assert!((change - 2.0 * (5.0 / 7.0 - 0.3)).abs() < 1e-9);
+        let tied = problem.solve_with_weights(&weights);
+        assert!((tied.net_flow(0).unwrap() - tied.net_flow(1).unwrap()).abs() < 1e-9);
+
+        // C is never better than B
+        assert_eq!(problem.min_weight_change_to_swap(1, 2), None);
+    }
+
+    #[test]
+    fn fast_method_with_tied_evaluations() {
+        // Many alternatives share evaluations, and their differences fall exactly on q and p
+        let values = [
+            0.0, 1.0, 1.0, 2.0, 2.0, 2.0, 3.0, 3.0, 4.0, 0.0, 4.0, 1.0, 0.1, 0.3,
+        ];
+        for (q, p) in [(1.0, 3.0), (0.0, 2.0), (0.1, 0.3), (1.0, 2.0)] {
+            let problem = PrometheeProblem::new(
+                AlternativeTable::from_matrix(values.iter().map(|&f| vec![f, f]).collect()),
+                vec![
+                    GeneralizedCriterion::Linear { q, p },
+                    GeneralizedCriterion::VShape { p },
+                ],
+                vec![1.0, 1.0],
+            );
+
+            for k in 0..2 {
+                let (fast_pos, fast_neg) = problem.fast_unicriterion_flows(k).unwrap();
+                let (slow_pos, slow_neg) = slow_flows(&problem, k);
+                assert_close(&slow_pos, &fast_pos, 1e-12);
+                assert_close(&slow_neg, &fast_neg, 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn promethee1_indifference_zone() {
+        let result = Promethee1Result::new(vec![0.5, 0.501, 0.2], vec![0.3, 0.3, 0.6]);
+        assert_eq!(result.relation(1, 0), Some(PartialPreference::Prefers));
+
+        let result = Promethee1Result::with_eps(vec![0.5, 0.501, 0.2], vec![0.3, 0.3, 0.6], 0.01);
+        assert_eq!(result.relation(1, 0), Some(PartialPreference::Indifferent));
+        assert_eq!(result.relation(0, 2), Some(PartialPreference::Prefers));
+
+        let problem = init_simple_problem();
+        let count = |result: &Promethee1Result, relation| {
+            result
+                .preferences()
+                .iter()
+                .flatten()
+                .filter(|&&r| r == relation)
+                .count()
+        };
+        let strict = problem.solve_partial_eps(0.0);
+        let wide = problem.solve_partial_eps(1.0);
+        assert!(
+            count(&wide, PartialPreference::Prefers) < count(&strict, PartialPreference::Prefers)
+        );
+        assert_eq!(
+            count(&wide, PartialPreference::Indifferent),
+            problem.n() * problem.n()
+        );
+    }
+
+    #[test]
+    fn asymmetric_linear_flows() {
+        let table = || init_simple_problem().alt_table.clone();
+        let linear = PrometheeProblem::new(
+            table(),
+            vec![GeneralizedCriterion::Linear { q: 0.5, p: 2.0 }; 2],
+            vec![1.0, 1.0],
+        )
+        .solve();
+        let symmetric = PrometheeProblem::new(
+            table(),
+            vec![
+                GeneralizedCriterion::AsymmetricLinear {
+                    q_pos: 0.5,
+                    p_pos: 2.0,
+                    q_neg: 0.5,
+                    p_neg: 2.0
+                };
+                2
+            ],
+            vec![1.0, 1.0],
+        )
+        .solve();
+        assert_close(&linear.positive_flows, &symmetric.positive_flows, 1e-12);
+        assert_close(&linear.negative_flows, &symmetric.negative_flows, 1e-12);
+
+        // Losses felt sooner than gains weigh more in the negative flows
+        let loss_averse = PrometheeProblem::new(
+            table(),
+            vec![
+                GeneralizedCriterion::AsymmetricLinear {
+                    q_pos: 0.5,
+                    p_pos: 2.0,
+                    q_neg: 0.0,
+                    p_neg: 1.0
+                };
+                2
+            ],
+            vec![1.0, 1.0],
+        )
+        .solve();
+        assert_eq!(loss_averse.positive_flows, symmetric.positive_flows);
+        assert!(
+            loss_averse.negative_flows.iter().sum::<f64>()
+                > symmetric.negative_flows.iter().sum::<f64>()
+        );
+    }
+
+    #[test]
+    fn asymmetric_linear_pairwise_paths_match_solve() {
+        let problem = PrometheeProblem::new(
+            init_simple_problem().alt_table.clone(),
+            vec![
+                GeneralizedCriterion::AsymmetricLinear {
+                    q_pos: 0.5,
+                    p_pos: 2.0,
+                    q_neg: 0.0,
+                    p_neg: 1.0
+                };
+                2
+            ],
+            vec![1.0, 1.0],
+        );
+        let solution = problem.solve();
+        let (verbose_solution, _) = problem.solve_verbose();
+        assert_close(
+            &solution.positive_flows,
+            &verbose_solution.positive_flows,
+            1e-12,
+        );
+        assert_close(
+            &solution.negative_flows,
+            &verbose_solution.negative_flows,
+            1e-12,
+        );
+
+        let row_flows: Vec<f64> = problem
+            .net_preference_matrix()
+            .iter()
+            .map(|row| row.iter().sum::<f64>() / (problem.n() as f64 - 1.0))
+            .collect();
+        assert_close(&solution.net_flows(), &row_flows, 1e-12);
+    }
+
+    #[test]
+    fn error_messages() {
+        let errors = [
+            (PrometheeError::EmptyTable, "Empty table of alternatives"),
+            (
+                PrometheeError::Parse {
+                    line: 3,
+                    message: "Missing value".to_string(),
+                },
+                "Parse error at line 3: Missing value",
+            ),
+            (
+                PrometheeError::DuplicateCriterionName("Price".to_string()),
+                "Duplicate criterion name 'Price'",
+            ),
+            (
+                PrometheeError::InvalidThresholds {
+                    criterion: 1,
+                    message: "Threshold p must be positive, got 0".to_string(),
+                },
+                "Invalid criterion 1: Threshold p must be positive, got 0",
+            ),
+            (
+                PrometheeError::DegenerateWeights { sum: 0.0 },
+                "Weights sum to 0, they cannot be normalized",
+            ),
+            (PrometheeError::NoCriteria, "No criterion declared"),
+            (
+                PrometheeError::UnknownCriterion("Comfort".to_string()),
+                "Unknown criterion 'Comfort'",
+            ),
+            (
+                PrometheeError::AlternativePerfCountMismatch {
+                    alt: "A".to_string(),
+                    expected: 2,
+                    got: 3,
+                },
+                "Alternative 'A' has 3 evaluations, 2 expected",
+            ),
+            (
+                PrometheeError::DimensionMismatch {
+                    field: "weights".to_string(),
+                    given: 1,
+                    expected: 2,
+                },
+                "Wrong number of weights, 1 given, 2 expected",
+            ),
+            (
+                PrometheeError::TooFewAlternatives { given: 1, min: 2 },
+                "1 alternatives given, at least 2 are needed",
+            ),
+            (
+                PrometheeError::NonFiniteEvaluation {
+                    alt: "B".to_string(),
+                    criterion: 0,
+                },
+                "Non finite evaluation of alternative 'B' on criterion 0",
+            ),
+        ];
+        for (error, message) in errors {
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn try_new_rejects_unusable_tables() {
+        let criteria = || vec![GeneralizedCriterion::Usual; 2];
+        let single = AlternativeTable::from_matrix(vec![vec![1.0, 2.0]]);
+        assert_eq!(
+            PrometheeProblem::try_new(single, criteria(), vec![1.0, 1.0]).unwrap_err(),
+            PrometheeError::TooFewAlternatives { given: 1, min: 2 }
+        );
+
+        let with_nan = AlternativeTable::from_matrix(vec![vec![1.0, 2.0], vec![f64::NAN, 1.0]]);
+        assert_eq!(
+            PrometheeProblem::try_new(with_nan, criteria(), vec![1.0, 1.0]).unwrap_err(),
+            PrometheeError::NonFiniteEvaluation {
+                alt: "a_2".to_string(),
+                criterion: 0
+            }
+        );
+    }
+
+    #[test]
+    fn single_criterion_rankings() {
+        let problem = init_simple_problem();
+        let rankings = problem.single_criterion_rankings();
+        assert_eq!(rankings.len(), problem.q());
+        assert_eq!(rankings[0][0], 0);
+        assert_eq!(rankings[1][0], 1);
+
+        for (k, ranking) in rankings.iter().enumerate() {
+            let column = problem.alt_table.criterion(k).unwrap();
+            let single = PrometheeProblem::new(
+                AlternativeTable::from_matrix(column.into_iter().map(|v| vec![v]).collect()),
+                vec![problem.generalized_criteria[k].clone()],
+                vec![1.0],
+            );
+            assert_eq!(
+                single.single_criterion_rankings()[0],
+                single.solve().ranked_alts()
+            );
+            assert_eq!(&single.single_criterion_rankings()[0], ranking);
+        }
+    }
+
+    #[test]
+    fn serde_rebuilds_cache() {
+        let problem = init_random_problem(
+            50,
+            0.0,
+            10.0,
+            vec![
+                GeneralizedCriterion::VShape { p: 2.0 },
+                GeneralizedCriterion::Usual,
+                GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+            ],
+        );
+        let json = serde_json::to_value(&problem).unwrap();
+        assert!(json.get("argsorted_eval_matrix").is_none());
+
+        let restored: PrometheeProblem = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            restored.argsorted_eval_matrix,
+            problem.argsorted_eval_matrix
+        );
+        assert!(restored.debug_verify_cache());
+        assert_eq!(restored.solve().net_flows(), problem.solve().net_flows());
+    }
+
+    #[test]
+    fn aggregate_then_rank_is_compensatory() {
+        // A is far better on the first criterion, B slightly better on the two others
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![100.0, 0.0, 0.0], vec![10.0, 1.0, 1.0]]),
+            vec![GeneralizedCriterion::VShape { p: 1.0 }; 3],
+            vec![1.0; 3],
+        );
+
+        assert_eq!(problem.solve().ranked_alts(), vec![1, 0]);
+        assert_eq!(problem.aggregate_then_rank(1.0), vec![0, 1]);
+    }
+
+    #[test]
+    fn sorted_by_criterion() {
+        let problem = init_simple_problem();
+        let sorted = problem.sorted_by_criterion(0);
+        assert_eq!(sorted[0], ("A", 3.0));
+        assert_eq!(sorted[1].1, 2.0);
+        assert_eq!(sorted[2].1, 2.0);
+        assert_eq!(
+            problem.sorted_by_criterion(1),
+            vec![("B", 4.0), ("C", 3.0), ("A", 1.0)]
+        );
+
+        // Usual criterion without cache, to minimize
+        let table = AlternativeTable::new(
+            vec![
+                Alternative::new("A".to_string(), vec![3.0]),
+                Alternative::new("B".to_string(), vec![1.0]),
+                Alternative::new("C".to_string(), vec![2.0]),
+            ]
+            .into(),
+        )
+        .with_criteria_directions(vec![OptimizationDirection::Min]);
+        let problem = PrometheeProblem::new_preserve_signs(
+            table,
+            vec![GeneralizedCriterion::Usual],
+            vec![1.0],
+        );
+        assert_eq!(
+            problem.sorted_by_criterion(0),
+            vec![("B", 1.0), ("C", 2.0), ("A", 3.0)]
+        );
+    }
+
+    #[test]
+    fn markdown_table() {
+        let problem = init_simple_problem();
+        let markdown = problem.to_markdown();
+        let lines: Vec<&str> = markdown.lines().collect();
+
+        // Header, separator, directions, weights, preference functions and one row per alternative
+        assert_eq!(lines.len(), 2 + 3 + problem.n());
+        assert!(lines
+            .iter()
+            .all(|line| line.starts_with('|') && line.ends_with('|')));
+        assert!(lines[0].contains("Criteria"));
+        assert!(lines[1].chars().all(|c| matches!(c, '|' | '-' | ':' | ' ')));
+        assert!(lines[4].contains("VShape(3)"));
+    }
+
+    #[test]
+    fn rounding_differences_are_ties() {
+        // 0.1 + 0.2 differs from 0.3 by rounding only
+        let result = Promethee2Result {
+            positive_flows: vec![0.1 + 0.2, 0.3, 0.1],
+            unicrit_positive_flows: vec![vec![0.1 + 0.2, 0.3, 0.1]],
+            negative_flows: vec![0.0; 3],
+            unicrit_negative_flows: vec![vec![0.0; 3]],
+        };
+        assert_ne!(result.net_flow(0), result.net_flow(1));
+        assert!(flows_equal(
+            result.net_flow(0).unwrap(),
+            result.net_flow(1).unwrap()
+        ));
+        assert!(!result.is_better(0, 1));
+        assert!(!result.is_better(1, 0));
+        assert!(result.is_better(1, 2));
+
+        // The tie is broken by index, not by the rounding error
+        assert_eq!(result.ranked_alts(), vec![0, 1, 2]);
+        let rows = init_simple_problem().full_ranking(&result);
+        assert_eq!(rows[0].rank, rows[1].rank);
+    }
+
+    #[test]
+    fn break_even_thresholds() {
+        let problem = init_simple_problem();
+        let with_p = |p: f64| {
+            PrometheeProblem::new(
+                problem.alt_table.clone(),
+                vec![
+                    problem.generalized_criteria[0].clone(),
+                    GeneralizedCriterion::Linear { q: 1.0, p },
+                ],
+                vec![3.0, 7.0],
+            )
+            .solve()
+        };
+
+        // C is better than A for p = 3, A gains ground as p grows since its large loss weighs less
+        let p = problem.break_even_p(1, 0, 2).unwrap();
+        assert!(p > 10.0 && p < 100.0);
+        let tied = with_p(p);
+        assert!((tied.net_flow(0).unwrap() - tied.net_flow(2).unwrap()).abs() < 1e-9);
+
+        // B and C only differ by q on the second criterion, they are tied for any p <= 2
+        assert_eq!(problem.break_even_p(1, 1, 2), Some(2.0));
+        // No threshold is needed to tie an alternative with itself
+        assert_eq!(problem.break_even_p(0, 0, 0), None);
+
+        let usual = PrometheeProblem::new(
+            problem.alt_table.clone(),
+            vec![GeneralizedCriterion::Usual; 2],
+            vec![1.0, 1.0],
+        );
+        assert_eq!(usual.break_even_p(0, 0, 1), None);
     }
 
-    /// Shift the evaluation of alternative a for criterion k by shift
-    /// This sorts the evaluation matrix for criterion k afterwards (naively)
-    pub fn shift_eval(&mut self, k: usize, i: usize, shift: f64) {
-        self.alt_table.shift_performance(i, k, shift);
-        self.argsort_evals(k);
+    #[test]
+    fn single_precision_flows() {
+        let problem = init_random_problem(
+            1000,
+            0.0,
+            100.0,
+            vec![
+                GeneralizedCriterion::Usual,
+                GeneralizedCriterion::UShape { p: 10.0 },
+                GeneralizedCriterion::VShape { p: 20.0 },
+            ],
+        );
+        let exact = problem.solve();
+        let single = problem.solve_f32();
+
+        assert_eq!(
+            single.unicrit_positive_flows[2],
+            exact.unicrit_positive_flows[2]
+        );
+        assert_close(&exact.positive_flows, &single.positive_flows, 1e-5);
+        assert_close(&exact.negative_flows, &single.negative_flows, 1e-5);
+        assert_close(&exact.net_flows(), &single.net_flows(), 1e-5);
     }
 
-    pub fn print(&self) {
-        println!(
-            "Promethee problem with {} alternatives and {} criteria",
-            self.n, self.q
+    #[test]
+    fn ideal_positioning() {
+        // A is the best on both criteria, C the worst on both
+        let table =
+            AlternativeTable::from_matrix(vec![vec![5.0, 1.0], vec![3.0, 2.0], vec![1.0, 4.0]])
+                .with_criteria_directions(vec![
+                    OptimizationDirection::Max,
+                    OptimizationDirection::Min,
+                ]);
+        let problem = PrometheeProblem::new_preserve_signs(
+            table,
+            vec![
+                GeneralizedCriterion::VShape { p: 4.0 },
+                GeneralizedCriterion::Usual,
+            ],
+            vec![1.0, 1.0],
         );
-        let mut builder = tabled::builder::Builder::default();
-        let crit_names = self.alt_table.criteria_names();
 
-        use std::iter::once;
+        let positions = problem.ideal_positioning();
+        assert_eq!(positions[0], (0.0, 1.0));
+        assert_eq!(positions[1], (0.5 * 0.5 + 0.5, 0.5 * 0.5 + 0.5));
+        assert_eq!(positions[2], (1.0, 0.0));
+    }
 
-        builder.push_record(
-            once("Criteria")
-                .chain(crit_names.iter().map(|s| s.as_ref()))
-                .collect::<Vec<_>>(),
+    #[test]
+    fn weights_ranked() {
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![1.0, 2.0, 3.0], vec![3.0, 2.0, 1.0]])
+                .with_criteria_names(vec!["Price".into(), "Speed".into(), "Comfort".into()]),
+            vec![GeneralizedCriterion::Usual; 3],
+            vec![2.0, 5.0, 3.0],
         );
 
-        builder.push_record(
-            once("Directions")
-                .chain(
-                    self.alt_table
-                        .criteria_directions()
-                        .iter()
-                        .map(|d| match d {
-                            OptimizationDirection::Min => "Min",
-                            OptimizationDirection::Max => "Max",
-                        }),
-                )
+        let ranked = problem.weights_ranked();
+        assert_eq!(
+            ranked
+                .iter()
+                .map(|&(k, name, _)| (k, name))
                 .collect::<Vec<_>>(),
+            vec![(1, "Speed"), (2, "Comfort"), (0, "Price")]
         );
+        assert!((ranked.iter().map(|(_, _, w)| w).sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!(ranked.windows(2).all(|pair| pair[0].2 >= pair[1].2));
+    }
 
-        builder.push_record(
-            once("Weights".to_string())
-                .chain(self.weights.iter().map(|&w| w.to_string()))
-                .collect::<Vec<_>>(),
+    #[test]
+    fn content_hash() {
+        let problem = init_simple_problem();
+        // Stable across runs
+        assert_eq!(problem.content_hash(), init_simple_problem().content_hash());
+        assert_eq!(problem.content_hash(), 6975621662800636910);
+
+        // B and C are tied on the first criterion, moving B away and back may swap them in the cache
+        let mut moved = init_simple_problem();
+        moved.shift_eval(0, 1, 1.0);
+        moved.shift_eval(0, 1, -1.0);
+        assert_eq!(moved.content_hash(), problem.content_hash());
+        let json = serde_json::to_string(&problem).unwrap();
+        let restored: PrometheeProblem = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.content_hash(), problem.content_hash());
+
+        let mut changed = init_simple_problem();
+        changed.shift_eval(1, 0, 0.5);
+        assert_ne!(changed.content_hash(), problem.content_hash());
+        let renamed = PrometheeProblem::new(
+            problem
+                .alt_table
+                .clone()
+                .with_criteria_names(vec!["Price".into(), "Speed".into()]),
+            problem.generalized_criteria.clone(),
+            problem.weights.clone(),
         );
+        assert_ne!(renamed.content_hash(), problem.content_hash());
+    }
 
-        builder.push_record(
-            once("Preference functions".to_string())
-                .chain(self.generalized_criteria.iter().map(|c| match c {
-                    GeneralizedCriterion::UShape { p } => format!("UShape({})", p),
-                    GeneralizedCriterion::VShape { p } => format!("VShape({})", p),
-                    GeneralizedCriterion::Linear { q, p } => format!("Linear({}, {})", q, p),
-                    GeneralizedCriterion::Usual => "Usual".to_string(),
-                }))
-                .collect::<Vec<_>>(),
+    #[test]
+    fn pairwise_preference_degrees_stay_bounded() {
+        // The public constructors reject the thresholds that would give degrees outside [0, 1], so the
+        // debug assertion of the pairwise method holds for every criterion it is used with
+        let mut problem = init_random_problem(
+            15,
+            -5.0,
+            10.0,
+            vec![
+                GeneralizedCriterion::Usual,
+                GeneralizedCriterion::UShape { p: 2.0 },
+                GeneralizedCriterion::AsymmetricLinear {
+                    q_pos: 0.5,
+                    p_pos: 2.0,
+                    q_neg: 0.0,
+                    p_neg: 1.0,
+                },
+            ],
         );
+        assert!(PrometheeProblem::try_new(
+            problem.alt_table.clone(),
+            vec![
+                GeneralizedCriterion::Usual,
+                GeneralizedCriterion::UShape { p: 2.0 },
+                GeneralizedCriterion::AsymmetricLinear {
+                    q_pos: f64::NAN,
+                    p_pos: 2.0,
+                    q_neg: 0.0,
+                    p_neg: 1.0,
+                },
+            ],
+            vec![1.0; 3],
+        )
+        .is_err());
 
-        // Print alternatives
-        for alternative in self.alt_table.alternatives() {
-            builder.push_record(
-                once(alternative.name().to_string())
-                    .chain(alternative.perfs().iter().map(|&v| v.to_string()))
-                    .collect::<Vec<_>>(),
-            );
+        problem.shift_eval(2, 4, 1e6);
+        let result = problem.solve();
+        for flows in result
+            .unicrit_positive_flows
+            .iter()
+            .chain(&result.unicrit_negative_flows)
+        {
+            assert!(flows.iter().all(|phi| (0.0..=1.0).contains(phi)));
         }
-        let mut table = builder.build();
-        table.with(Style::modern());
+        assert!(result
+            .net_flows()
+            .iter()
+            .all(|phi| (-1.0..=1.0).contains(phi)));
+    }
 
-        println!("{}", table);
+    #[test]
+    fn minimal_criteria_for_top() {
+        // The second criterion is constant and the third one agrees with the first
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![
+                vec![5.0, 1.0, 4.0, 1.0],
+                vec![3.0, 1.0, 3.0, 5.0],
+                vec![1.0, 1.0, 1.0, 2.0],
+            ]),
+            vec![
+                GeneralizedCriterion::VShape { p: 2.0 },
+                GeneralizedCriterion::Usual,
+                GeneralizedCriterion::Linear { q: 0.5, p: 2.0 },
+                GeneralizedCriterion::Usual,
+            ],
+            vec![4.0, 3.0, 2.0, 1.0],
+        );
+        assert_eq!(problem.solve().ranked_alts()[0], 0);
+
+        let subset = problem.minimal_criteria_for_top(1);
+        assert!(!subset.contains(&1));
+        assert_eq!(problem.solve_subset(&subset).ranked_alts()[0], 0);
+
+        let subset = problem.minimal_criteria_for_top(3);
+        assert!(!subset.contains(&1));
+        let mut top = problem.solve_subset(&subset).ranked_alts();
+        top.sort_unstable();
+        assert_eq!(top, vec![0, 1, 2]);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use alternatives::{Alternative, AlternativeTable};
+    #[test]
+    fn group_preference() {
+        let problem = init_simple_problem();
+        let (group_a, group_bc) = ([0], [1, 2]);
 
-    fn init_simple_problem() -> PrometheeProblem {
+        let a_over_bc = problem.group_preference(&group_a, &group_bc);
+        let bc_over_a = problem.group_preference(&group_bc, &group_a);
+        let expected =
+            (problem.pairwise_preference(0, 1) + problem.pairwise_preference(0, 2)) / 2.0;
+        assert!((a_over_bc - expected).abs() < 1e-12);
+        // B and C are much better than A on the heavier second criterion
+        assert!(bc_over_a > a_over_bc);
+        assert_eq!(problem.group_preference(&[1], &[1]), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Groups must not be empty")]
+    fn group_preference_empty_group() {
+        init_simple_problem().group_preference(&[0], &[]);
+    }
+
+    #[test]
+    fn ranked_alts_ties_by_ascending_index() {
+        // B and C have the same net flow, as well as A and D
+        let result = Promethee2Result {
+            positive_flows: vec![0.2, 0.5, 0.5, 0.2],
+            unicrit_positive_flows: vec![vec![0.2, 0.5, 0.5, 0.2]],
+            negative_flows: vec![0.0; 4],
+            unicrit_negative_flows: vec![vec![0.0; 4]],
+        };
+        assert_eq!(result.ranked_alts(), vec![1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn profile_distance_matrix() {
+        let result = init_simple_problem().solve();
+        let distances = result.profile_distance_matrix();
+
+        assert_eq!(distances.len(), 3);
+        for (a, row) in distances.iter().enumerate() {
+            assert_eq!(row[a], 0.0);
+            for (b, d) in row.iter().enumerate() {
+                assert_eq!(*d, distances[b][a]);
+            }
+        }
+
+        let (pa, pb) = (result.profile(0).unwrap(), result.profile(1).unwrap());
+        let expected = ((pa[0] - pb[0]).powi(2) + (pa[1] - pb[1]).powi(2)).sqrt();
+        assert!((distances[0][1] - expected).abs() < 1e-12);
+        assert!(distances[0][1] > 0.0);
+        assert_eq!(result.profile(3), None);
+    }
+
+    #[test]
+    fn missing_evaluation_is_neutral() {
         let alt_table = AlternativeTable::new(
             vec![
                 Alternative::new("A".to_string(), vec![3.0, 1.0]),
-                Alternative::new("B".to_string(), vec![2.0, 4.0]),
+                Alternative::new("B".to_string(), vec![2.0, f64::NAN]),
                 Alternative::new("C".to_string(), vec![2.0, 3.0]),
             ]
             .into(),
         );
-        // let prob_matrix: Vec<Vec<f64>> = vec![vec![3.0, 2.0, 2.0], vec![1.0, 4.0, 3.0]];
-        let weights: Vec<f64> = vec![3.0, 7.0];
-        let criteria: Vec<GeneralizedCriterion> = vec![
+        let criteria = vec![
             GeneralizedCriterion::VShape { p: 3.0 },
             GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
         ];
+        let problem = builder::PrometheeProblemBuilder::new()
+            .criterion("c0", OptimizationDirection::Max, criteria[0].clone(), 3.0)
+            .and_then(|b| b.criterion("c1", OptimizationDirection::Max, criteria[1].clone(), 7.0))
+            .and_then(|b| b.alternative("A", vec![3.0, 1.0]))
+            .and_then(|b| b.alternative("B", vec![2.0, f64::NAN]))
+            .and_then(|b| b.alternative("C", vec![2.0, 3.0]))
+            .unwrap()
+            .allow_missing(true)
+            .build()
+            .unwrap();
 
-        PrometheeProblem::new(alt_table, criteria, weights)
+        // No preference either way on the criterion where B is missing
+        assert_eq!(problem.pairwise_breakdown(1, 0)[1], 0.0);
+        assert_eq!(problem.pairwise_breakdown(0, 1)[1], 0.0);
+        assert!(problem.pairwise_breakdown(0, 1)[0] > 0.0);
+
+        // B gets zero flows on this criterion, A and C are compared as if B was not there
+        let result = problem.solve();
+        assert_eq!(result.unicrit_positive_flows[1][1], 0.0);
+        assert_eq!(result.unicrit_negative_flows[1][1], 0.0);
+        let without_b = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![1.0], vec![3.0]]),
+            vec![GeneralizedCriterion::Linear { q: 1.0, p: 3.0 }],
+            vec![1.0],
+        )
+        .solve();
+        assert_close(
+            &[
+                result.unicrit_positive_flows[1][0],
+                result.unicrit_positive_flows[1][2],
+            ],
+            &without_b.positive_flows,
+            1e-12,
+        );
+        assert_close(
+            &problem.solve_positive_only(),
+            &result.positive_flows,
+            1e-12,
+        );
+        let (verbose_result, _) = problem.solve_verbose();
+        assert_close(&verbose_result.net_flows(), &result.net_flows(), 1e-12);
+
+        // Sorting and suggestions skip the missing value instead of panicking
+        assert!(problem.sorted_evals(1)[2].is_nan());
+        assert_eq!(problem.sorted_by_criterion(1)[2].0, "B");
+        assert_eq!(
+            PrometheeProblem::suggest_criteria(&alt_table)[1],
+            GeneralizedCriterion::Usual
+        );
+
+        // Missing values are rejected by try_new, and accepted by the builder only if allowed
+        assert!(PrometheeProblem::try_new(alt_table, criteria, vec![3.0, 7.0]).is_err());
+        let built = problem.to_builder().build().unwrap();
+        assert_close(&built.solve().net_flows(), &result.net_flows(), 1e-12);
+
+        // The fast method is used again once the value is given
+        let mut problem = problem;
+        problem.set_eval(1, 1, 4.0);
+        assert!(problem.debug_verify_cache());
+        assert_close(
+            &problem.solve().net_flows(),
+            &init_simple_problem().solve().net_flows(),
+            1e-12,
+        );
     }
 
-    fn round_vec(v: &mut Vec<f64>) -> Vec<f64> {
-        v.iter().map(|fl| (fl * 1000.0).round() / 1000.0).collect()
+    #[test]
+    fn ranking_kendall_tau() {
+        assert_eq!(
+            super::ranking_kendall_tau(&[0, 1, 2, 3], &[0, 1, 2, 3]),
+            1.0
+        );
+        assert_eq!(
+            super::ranking_kendall_tau(&[0, 1, 2, 3], &[3, 2, 1, 0]),
+            -1.0
+        );
+        assert!((super::ranking_kendall_tau(&[0, 1, 2], &[1, 0, 2]) - 1.0 / 3.0).abs() < 1e-12);
     }
 
     #[test]
-    fn test_solve() {
+    fn ranking_spearman() {
+        assert_eq!(super::ranking_spearman(&[0, 1, 2, 3], &[0, 1, 2, 3]), 1.0);
+        assert_eq!(super::ranking_spearman(&[0, 1, 2, 3], &[3, 2, 1, 0]), -1.0);
+        assert_eq!(super::ranking_spearman(&[0, 1, 2], &[1, 0, 2]), 0.5);
+    }
+
+    #[test]
+    fn flows_spearman() {
+        assert_eq!(
+            super::flows_spearman(&[3.0, 2.0, 1.0], &[0.5, 0.2, 0.1]),
+            1.0
+        );
+        assert_eq!(
+            super::flows_spearman(&[3.0, 2.0, 1.0], &[0.1, 0.2, 0.5]),
+            -1.0
+        );
+        // The tied alternatives share the rank 1.5
+        assert!(
+            (super::flows_spearman(&[3.0, 2.0, 1.0], &[1.0, 1.0, 0.0]) - 3f64.sqrt() / 2.0).abs()
+                < 1e-12
+        );
+        assert_eq!(
+            super::flows_spearman(&[3.0, 2.0, 1.0], &[0.0, 0.0, 0.0]),
+            0.0
+        );
+    }
+
+    #[test]
+    fn criterion_alignment() {
+        let problem = init_random_problem(20, 0.0, 10.0, vec![GeneralizedCriterion::Usual]);
+        let alignment = problem.criterion_alignment(&problem.solve());
+        assert_eq!(alignment, vec![1.0]);
+
         let problem = init_simple_problem();
+        let alignment = problem.criterion_alignment(&problem.solve());
+        assert_eq!(alignment.len(), 2);
+        // B wins, as on the second criterion which has most of the weight
+        assert!((alignment[1] - 1.0).abs() < 1e-12);
+        // The first criterion prefers A, ranked last, and ties B and C
+        assert!((alignment[0] + 3f64.sqrt() / 2.0).abs() < 1e-12);
+    }
 
-        let solution = problem.solve();
-        let final_net_flow: Vec<f64> = solution
-            .net_flows()
-            .iter()
-            .map(|fl| (fl * 1000.0).round() / 1000.0)
-            .collect();
-        let real_solution = vec![-0.425, 0.3, 0.125];
-        let mut equality = real_solution
-            .iter()
-            .enumerate()
-            .map(|(i, &val)| val == final_net_flow[i]);
+    #[test]
+    fn relative_to() {
+        let result = init_simple_problem().solve();
+        let net_flows = result.net_flows();
+        let relative = result.relative_to(2);
 
-        assert!(equality.all(|x| x))
+        assert_eq!(relative[2], 0.0);
+        assert_eq!(relative[0], net_flows[0] - net_flows[2]);
+        assert!(relative[1] > 0.0);
     }
 
     #[test]
-    fn test_solve_2() {
-        let alt_table = AlternativeTable::new(
+    #[should_panic(expected = "Reference alternative out of range")]
+    fn relative_to_out_of_range() {
+        init_simple_problem().solve().relative_to(3);
+    }
+
+    #[test]
+    fn pairwise_entropy() {
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![5.0, 5.0], vec![1.0, 4.99], vec![1.0, 1.0]]),
             vec![
-                Alternative::new("A".to_string(), vec![3.0, 1.0]),
-                Alternative::new("B".to_string(), vec![2.0, 4.0]),
-                Alternative::new("C".to_string(), vec![0.0, 5.0]),
-            ]
-            .into(),
+                GeneralizedCriterion::Usual,
+                GeneralizedCriterion::VShape { p: 4.0 },
+            ],
+            vec![1.0, 1.0],
         );
-        // let prob_matrix: Vec<Vec<f64>> = vec![vec![3.0, 2.0, 0.0], vec![1.0, 4.0, 5.0]];
-        let weights: Vec<f64> = vec![1.0, 1.0];
-        let criteria: Vec<GeneralizedCriterion> = vec![
-            GeneralizedCriterion::VShape { p: 2.0 },
-            GeneralizedCriterion::VShape { p: 3.0 },
-        ];
 
-        let problem = PrometheeProblem::new(alt_table, criteria, weights);
+        // The first alternative beats the second one almost only on the first criterion
+        assert!(problem.pairwise_entropy(0, 1) < 0.05);
+        // and the third one equally on both criteria
+        assert!((problem.pairwise_entropy(0, 2) - 2f64.ln()).abs() < 1e-12);
+        // The third alternative is not preferred to the first one at all
+        assert_eq!(problem.pairwise_entropy(2, 0), 0.0);
+    }
 
-        let solution = problem.solve();
-        let final_net_flow: Vec<f64> = solution
-            .net_flows()
-            .iter()
-            .map(|fl| (fl * 1000.0).round() / 1000.0)
-            .collect();
-        let real_solution = vec![-0.125, 0.292, -0.167];
-        // println!("{:#?}", final_net_flow);
-        let mut equality = real_solution
-            .iter()
-            .enumerate()
-            .map(|(i, &val)| val == final_net_flow[i]);
+    #[test]
+    fn ranked_by_positive_and_negative_flow() {
+        // The first alternative dominates the others the most, but is also the most dominated
+        let result = Promethee2Result {
+            positive_flows: vec![0.6, 0.5, 0.1],
+            unicrit_positive_flows: vec![],
+            negative_flows: vec![0.5, 0.1, 0.6],
+            unicrit_negative_flows: vec![],
+        };
 
-        assert!(equality.all(|x| x))
+        assert_eq!(result.ranked_alts(), vec![1, 0, 2]);
+        assert_eq!(result.ranked_by_positive_flow(), vec![0, 1, 2]);
+        assert_eq!(result.ranked_by_negative_flow(), vec![1, 0, 2]);
+
+        let simple = init_simple_problem().solve();
+        assert_eq!(simple.ranked_by_positive_flow()[0], simple.ranked_alts()[0]);
     }
 
     #[test]
-    fn solve_fast_and_slow_equivalent() {
+    fn check_direction_consistency() {
         let problem = init_simple_problem();
+        assert_eq!(problem.check_direction_consistency(), Ok(()));
 
-        for k in 0..problem.q {
-            let generalized_criterion = &problem.generalized_criteria[k];
-            let dist_mat: Vec<Vec<f64>> = problem
-                .alt_table
-                .criterion(k)
-                .unwrap()
-                .iter()
-                .map(|&a_i| {
-                    problem
-                        .alt_table
-                        .criterion(k)
-                        .unwrap()
-                        .iter()
-                        .map(move |&a_j| a_i - a_j.to_owned())
-                        .collect()
-                })
-                .collect();
-            let (mut slow_pos_flow, mut slow_neg_flow) =
-                problem.slow_unicriterion_flows(&dist_mat, generalized_criterion);
-            slow_pos_flow = round_vec(&mut slow_pos_flow);
-            slow_neg_flow = round_vec(&mut slow_neg_flow);
+        let problem =
+            problem.with_declared_directions(vec![Some(OptimizationDirection::Min), None]);
+        assert_eq!(problem.check_direction_consistency(), Err(vec![0]));
+    }
 
-            let (mut fast_pos_flow, mut fast_neg_flow) =
-                problem.fast_unicriterion_flows(k).unwrap();
-            fast_pos_flow = round_vec(&mut fast_pos_flow);
-            fast_neg_flow = round_vec(&mut fast_neg_flow);
+    #[test]
+    fn flow_dispersion() {
+        let result = init_simple_problem().solve();
+        let dispersion = result.flow_dispersion_with_weights(&[3.0, 7.0]);
+        let net_flows = result.net_flows();
 
-            println!("{:#?}", slow_pos_flow);
-            println!("{:#?}", slow_neg_flow);
-            println!("");
+        assert_eq!(dispersion.len(), 3);
+        for (d, net_flow) in dispersion.iter().zip(&net_flows) {
+            assert!((d.mean * 2.0 - net_flow).abs() < 1e-12);
+            assert!(d.min <= d.mean && d.mean <= d.max);
+            assert!((d.std - (d.max - d.min) / 2.0).abs() < 1e-12);
+        }
+    }
 
-            println!("{:#?}", fast_pos_flow);
-            println!("{:#?}", fast_neg_flow);
+    #[test]
+    #[should_panic(expected = "Wrong number of weights given, 3 given, 2 expected")]
+    fn flow_dispersion_wrong_weights() {
+        init_simple_problem()
+            .solve()
+            .flow_dispersion_with_weights(&[3.0, 7.0, 1.0]);
+    }
 
-            assert!(slow_pos_flow
-                .iter()
-                .enumerate()
-                .map(|(i, &val)| val == fast_pos_flow[i])
-                .all(|x| x));
+    #[test]
+    fn transform_criterion() {
+        let mut problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![1.0, 3.0], vec![10.0, 2.0], vec![1000.0, 0.0]]),
+            vec![
+                GeneralizedCriterion::VShape { p: 100.0 },
+                GeneralizedCriterion::VShape { p: 3.0 },
+            ],
+            vec![1.0, 1.0],
+        );
+        // The huge value of the third alternative on the first criterion makes it win
+        assert_eq!(problem.solve().ranked_alts()[0], 2);
 
-            assert!(slow_neg_flow
+        // Once the first criterion is log-transformed, the differences on it are small
+        problem.transform_criterion(0, f64::ln);
+        assert!(problem.debug_verify_cache());
+        assert_eq!(problem.solve().ranked_alts()[0], 0);
+
+        // Decreasing transformations reorder the cache
+        problem.transform_criterion(0, |f| -f);
+        assert!(problem.debug_verify_cache());
+        assert_eq!(problem.sorted_with_indices(0)[0].0, 2);
+    }
+
+    #[test]
+    fn leaving_flow_contributions() {
+        let problem = init_random_problem(
+            15,
+            0.0,
+            10.0,
+            vec![
+                GeneralizedCriterion::VShape { p: 3.0 },
+                GeneralizedCriterion::Usual,
+                GeneralizedCriterion::Linear { q: 1.0, p: 4.0 },
+            ],
+        );
+        let result = problem.solve();
+
+        for ai in 0..problem.n() {
+            let contributions = problem.leaving_flow_contributions(ai);
+            let positive_flow: f64 = contributions
                 .iter()
-                .enumerate()
-                .map(|(i, &val)| val == fast_neg_flow[i])
-                .all(|x| x));
+                .zip(&problem.weights)
+                .map(|(c, w)| w * c / (problem.n() - 1) as f64)
+                .sum();
+            assert!((positive_flow - result.positive_flows[ai]).abs() < 1e-12);
         }
     }
+
+    #[test]
+    fn round_evaluations() {
+        let mut problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![
+                vec![1.0004, 2.0],
+                vec![1.0001, 2.0],
+                vec![0.5, 1.0],
+            ]),
+            vec![GeneralizedCriterion::Usual, GeneralizedCriterion::Usual],
+            vec![1.0, 1.0],
+        );
+        let net_flows = problem.solve().net_flows();
+        assert!(net_flows[0] > net_flows[1]);
+
+        problem.round_evaluations(2);
+        assert!(problem.debug_verify_cache());
+        let result = problem.solve();
+        assert_eq!(result.net_flow(0), result.net_flow(1));
+        assert_eq!(result.ranked_alts(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn ranked_with_relations() {
+        let problem = PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![2.0], vec![4.0], vec![1.0], vec![2.0 + 1e-6]]),
+            vec![GeneralizedCriterion::VShape { p: 4.0 }],
+            vec![1.0],
+        );
+        let result = problem.solve();
+
+        assert_eq!(
+            result.ranked_groups(1e-3),
+            vec![vec![1], vec![0, 3], vec![2]]
+        );
+        assert_eq!(
+            result.ranked_with_relations(1e-3),
+            vec![
+                (1, RankRelation::First),
+                (0, RankRelation::WorseThanPrevious),
+                (3, RankRelation::TiedWithPrevious),
+                (2, RankRelation::WorseThanPrevious),
+            ]
+        );
+        // With a smaller eps, the middle alternatives are no longer tied
+        assert_eq!(
+            result.ranked_with_relations(FLOW_EPS)[1],
+            (3, RankRelation::WorseThanPrevious)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong alternative index used, 3>=3")]
+    fn shift_eval_out_of_range_alternative() {
+        init_simple_problem().shift_eval(0, 3, 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong criterion index used, 2>=2")]
+    fn set_eval_out_of_range_criterion() {
+        init_simple_problem().set_eval(2, 0, 1.0);
+    }
 }
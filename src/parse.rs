@@ -1,95 +1,466 @@
-use calamine::{open_workbook, DataType, HeaderRow, Reader, Xlsx};
 use crate::alternatives::{Alternative, AlternativeTable, OptimizationDirection};
-use crate::{PrometheeProblem, generalized_criterion};
-use std::{error::Error, str::FromStr};
+use crate::{generalized_criterion, PrometheeError, PrometheeProblem};
+use calamine::{open_workbook, open_workbook_auto, Data, DataType, HeaderRow, Range, Reader, Xlsx};
+use serde::Deserialize;
+use std::{error::Error, fmt, path::Path, str::FromStr};
 
+/// Name of the worksheet containing the problem
+const SHEET_NAME: &str = "Promethee";
 
+/// Number of rows before the alternatives: header, directions, weights, function types, qs and ps
+const N_METADATA_ROWS: usize = 6;
+
+/// Row of metadata on the criteria, identified by its label in the first column. Labels are only looked for
+/// in the leading block of rows, before the first alternative, and each one only until its row is found:
+/// an alternative may then be named like a metadata row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MetadataRow {
+    Directions,
+    Weights,
+    Functions,
+    Qs,
+    Ps,
+    /// Optional row declaring each criterion as a cost or a benefit, see `PrometheeProblem::check_direction_consistency`
+    Kinds,
+}
+
+impl MetadataRow {
+    const ALL: [MetadataRow; 6] = [
+        MetadataRow::Directions,
+        MetadataRow::Weights,
+        MetadataRow::Functions,
+        MetadataRow::Qs,
+        MetadataRow::Ps,
+        MetadataRow::Kinds,
+    ];
+
+    /// Accepted labels, compared case-insensitively
+    fn labels(self) -> &'static [&'static str] {
+        match self {
+            MetadataRow::Directions => &["Direction", "Directions", "Min/Max"],
+            MetadataRow::Weights => &["Weight", "Weights", "Poids"],
+            MetadataRow::Functions => &["Function", "Functions", "Type", "Types", "Fonction"],
+            MetadataRow::Qs => &["q", "qs"],
+            MetadataRow::Ps => &["p", "ps"],
+            MetadataRow::Kinds => &["Kind", "Kinds", "Cost/Benefit"],
+        }
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        let label = label.trim();
+        Self::ALL.into_iter().find(|row| {
+            row.labels()
+                .iter()
+                .any(|candidate| candidate.eq_ignore_ascii_case(label))
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The file could not be opened or read as a workbook
+    Workbook(String),
+    MissingSheet(String),
+    /// The sheet does not have the expected rows and columns
+    InvalidLayout(String),
+    /// A metadata row is missing, along with the main label expected in its first column
+    MissingRow(String),
+    /// Invalid cell, rows and columns are counted from 0 starting at the header row
+    InvalidCell {
+        row: usize,
+        col: usize,
+        message: String,
+    },
+    NoAlternatives,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Workbook(e) => write!(f, "Could not read workbook: {}", e),
+            ParseError::MissingSheet(name) => write!(f, "Missing worksheet '{}'", name),
+            ParseError::InvalidLayout(e) => write!(f, "Invalid layout: {}", e),
+            ParseError::MissingRow(label) => write!(f, "Missing row '{}'", label),
+            ParseError::InvalidCell { row, col, message } => {
+                write!(
+                    f,
+                    "Invalid cell at row {}, column {}: {}",
+                    row, col, message
+                )
+            }
+            ParseError::NoAlternatives => write!(f, "No alternative given"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Read the problem of the "Promethee" worksheet of an Excel file. A q or p cell may hold the keyword
+/// "auto": p is then the interquartile range of the evaluations of the criterion (their range if it is zero)
+/// and q a quarter of this p.
 pub fn from_excel(file_path: &str) -> Result<PrometheeProblem, Box<dyn Error>> {
     let mut workbook: Xlsx<_> = open_workbook(file_path)?;
 
+    if !workbook.sheet_names().iter().any(|name| name == SHEET_NAME) {
+        return Err(ParseError::MissingSheet(SHEET_NAME.to_string()).into());
+    }
+    let range = workbook
+        .with_header_row(HeaderRow::FirstNonEmptyRow)
+        .worksheet_range(SHEET_NAME)?;
+
+    Ok(problem_from_range(&range)?)
+}
+
+/// Read every worksheet of an Excel file laid out as a Promethee problem, returning the pairs (sheet name, problem).
+/// Worksheets that do not follow the expected layout are skipped, with the reason written to stderr.
+pub fn from_excel_all(file_path: &str) -> Result<Vec<(String, PrometheeProblem)>, Box<dyn Error>> {
+    let mut workbook: Xlsx<_> = open_workbook(file_path)?;
+    workbook.with_header_row(HeaderRow::FirstNonEmptyRow);
+
+    let mut problems = Vec::new();
+    for sheet_name in workbook.sheet_names() {
+        let problem = workbook
+            .worksheet_range(&sheet_name)
+            .map_err(|e| ParseError::Workbook(e.to_string()))
+            .and_then(|range| problem_from_range(&range));
+
+        match problem {
+            Ok(problem) => problems.push((sheet_name, problem)),
+            Err(e) => eprintln!("Skipping worksheet '{}': {}", sheet_name, e),
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Read a problem from a spreadsheet file (xlsx, xls or ods), without ever panicking on malformed input.
+/// This is the entry point to use on untrusted files.
+pub fn try_from_file<P: AsRef<Path>>(file_path: P) -> Result<PrometheeProblem, ParseError> {
+    let mut workbook =
+        open_workbook_auto(file_path).map_err(|e| ParseError::Workbook(e.to_string()))?;
+
+    if !workbook.sheet_names().iter().any(|name| name == SHEET_NAME) {
+        return Err(ParseError::MissingSheet(SHEET_NAME.to_string()));
+    }
     let range = workbook
         .with_header_row(HeaderRow::FirstNonEmptyRow)
-        .worksheet_range("Promethee")?;
+        .worksheet_range(SHEET_NAME)
+        .map_err(|e| ParseError::Workbook(e.to_string()))?;
+
+    problem_from_range(&range)
+}
+
+fn cell_float(data: &Data, row: usize, col: usize) -> Result<f64, ParseError> {
+    match data.get_float() {
+        Some(val) if val.is_finite() => Ok(val),
+        _ => Err(ParseError::InvalidCell {
+            row,
+            col,
+            message: format!("Expected a finite number, got '{}'", data),
+        }),
+    }
+}
+
+fn cell_string(data: &Data, row: usize, col: usize) -> Result<&str, ParseError> {
+    data.get_string().ok_or_else(|| ParseError::InvalidCell {
+        row,
+        col,
+        message: format!("Expected a string, got '{}'", data),
+    })
+}
+
+/// Keyword accepted in the q and p rows to compute the threshold from the evaluations of the criterion
+const AUTO_THRESHOLD: &str = "auto";
+
+/// Ratio of an "auto" q to the "auto" p of the same criterion. This rule of thumb of the crate, not of the
+/// PROMETHEE method, keeps q below p as a Linear criterion requires, with a ramp over most of [0, p].
+const AUTO_Q_RATIO: f64 = 0.25;
+
+/// Threshold given in the q or p row, resolved against the evaluations of the criterion once they are read
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Threshold {
+    Value(f64),
+    /// Keyword "auto": p is `PrometheeProblem::auto_threshold` of the evaluations, the interquartile range
+    /// or the range if it is zero, and q is `AUTO_Q_RATIO` times this p
+    Auto,
+    /// Percentage of the span (max - min) of the evaluations, e.g. "20%", stored as a fraction
+    SpanFraction(f64),
+}
+
+/// Read a threshold cell: a number, the keyword "auto" or a percentage of the span like "20%"
+fn cell_threshold(data: &Data, row: usize, col: usize) -> Result<Threshold, ParseError> {
+    match data.get_string().map(str::trim) {
+        Some(s) if s.eq_ignore_ascii_case(AUTO_THRESHOLD) => Ok(Threshold::Auto),
+        Some(s) if s.ends_with('%') => s[..s.len() - 1]
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|pct| pct.is_finite())
+            .map(|pct| Threshold::SpanFraction(pct / 100.0))
+            .ok_or_else(|| ParseError::InvalidCell {
+                row,
+                col,
+                message: format!("Invalid percentage '{}'", s),
+            }),
+        _ => cell_float(data, row, col).map(Threshold::Value),
+    }
+}
+
+fn problem_from_range(range: &Range<Data>) -> Result<PrometheeProblem, ParseError> {
+    if range.width() < 2 {
+        return Err(ParseError::InvalidLayout(
+            "Expected a column of names and at least one criterion".to_string(),
+        ));
+    }
+    if range.height() < N_METADATA_ROWS {
+        return Err(ParseError::InvalidLayout(format!(
+            "Expected at least {} rows, got {}",
+            N_METADATA_ROWS,
+            range.height()
+        )));
+    }
 
     let ncrits = range.width() - 1;
-    let mut weights: Vec<f64> = Vec::with_capacity(ncrits);
     let mut pref_funs = Vec::with_capacity(ncrits);
-    let mut fun_types: Vec<&str> = Vec::with_capacity(ncrits);
-    let mut qs = Vec::with_capacity(ncrits);
-    let mut ps = Vec::with_capacity(ncrits);
 
     let criteria_names: Vec<String> = range
         .headers()
         .map(|headers| headers.into_iter().skip(1).collect())
-        .expect("To have headers");
+        .ok_or_else(|| ParseError::InvalidLayout("Missing header row".to_string()))?;
 
     let mut alternatives: Vec<Alternative> = Vec::new();
-    let mut criteria_directions: Vec<OptimizationDirection> = Vec::new();
+    // Index of each metadata row, found by its label rather than its position in the leading block of rows
+    let mut metadata_rows: [Option<usize>; 6] = [None; 6];
 
     for (i, row) in range.rows().enumerate() {
-        if i == 0 {
+        if row.len() != ncrits + 1 {
+            return Err(ParseError::InvalidLayout(format!(
+                "Invalid number of columns at row {}, {} expected, {} found",
+                i,
+                ncrits + 1,
+                row.len()
+            )));
+        }
+
+        if i == 0 || row.iter().all(|data| data.is_empty()) {
             continue;
-        } else if i == 1 {
-            let (_, directions) = row.split_at(1);
-            criteria_directions = directions
-                .into_iter()
-                .map(|data_dir| {
-                    OptimizationDirection::from_str(
-                        data_dir.get_string().expect("to be string"),
-                    )
-                    .expect("to be valid direction")
-                })
-                .collect();
-        } else if i == 2 {
-            let (_, ws) = row.split_at(1);
-            weights = ws
-                .into_iter()
-                .map(|data_w| data_w.get_float().expect("to be f64"))
-                .collect();
-        } else if i == 3 {
-            let (_, ftypes) = row.split_at(1);
-            fun_types = ftypes
-                .into_iter()
-                .map(|data_ft| data_ft.get_string().expect("to be string"))
-                .collect();
-        } else if i == 4 {
-            let (_, q_data) = row.split_at(1);
-            qs = q_data
-                .into_iter()
-                .map(|q| q.get_float().expect("to be f64"))
-                .collect();
-        } else if i == 5 {
-            let (_, p_data) = row.split_at(1);
-            ps = p_data
-                .into_iter()
-                .map(|p| p.get_float().expect("to be f64"))
-                .collect();
-        } else {
-            if row.len() != ncrits + 1 {
-                return Err("Invalid number of columns".into());
+        }
+        let metadata_row = row[0]
+            .get_string()
+            .and_then(MetadataRow::from_label)
+            .filter(|&metadata_row| {
+                alternatives.is_empty() && metadata_rows[metadata_row as usize].is_none()
+            });
+        match metadata_row {
+            Some(metadata_row) => metadata_rows[metadata_row as usize] = Some(i),
+            None => {
+                let name = cell_string(&row[0], i, 0)?;
+                let performances = row
+                    .iter()
+                    .enumerate()
+                    .skip(1)
+                    .map(|(k, data)| cell_float(data, i, k))
+                    .collect::<Result<_, _>>()?;
+                alternatives.push(Alternative::new(name.to_string(), performances));
             }
-            let name = row[0].get_string().expect("To be string");
-            let performances = row
-                .into_iter()
-                .skip(1)
-                .map(|data| data.get_float().expect("to be f64"))
-                .collect();
-            println!("{:?}", performances);
-            alternatives.push(Alternative::new(name.to_string(), performances));
         }
     }
 
+    // Rows are only parsed once they are all found, to report a missing row before invalid cells
+    let find = |metadata_row: MetadataRow| {
+        metadata_rows[metadata_row as usize]
+            .ok_or_else(|| ParseError::MissingRow(metadata_row.labels()[0].to_string()))
+    };
+    let directions_row = find(MetadataRow::Directions)?;
+    let weights_row = find(MetadataRow::Weights)?;
+    let functions_row = find(MetadataRow::Functions)?;
+    let qs_row = find(MetadataRow::Qs)?;
+    let ps_row = find(MetadataRow::Ps)?;
+    let kinds_row = metadata_rows[MetadataRow::Kinds as usize];
+    let cells = |i: usize| range.rows().nth(i).unwrap().iter().enumerate().skip(1);
+
+    let criteria_directions: Vec<OptimizationDirection> = cells(directions_row)
+        .map(|(k, data_dir)| {
+            OptimizationDirection::from_str(cell_string(data_dir, directions_row, k)?).map_err(
+                |message| ParseError::InvalidCell {
+                    row: directions_row,
+                    col: k,
+                    message,
+                },
+            )
+        })
+        .collect::<Result<_, _>>()?;
+    let weights: Vec<f64> = cells(weights_row)
+        .map(|(k, data_w)| cell_float(data_w, weights_row, k))
+        .collect::<Result<_, _>>()?;
+    let fun_types: Vec<&str> = cells(functions_row)
+        .map(|(k, data_ft)| cell_string(data_ft, functions_row, k))
+        .collect::<Result<_, _>>()?;
+    let qs: Vec<Threshold> = cells(qs_row)
+        .map(|(k, q)| cell_threshold(q, qs_row, k))
+        .collect::<Result<_, _>>()?;
+    let ps: Vec<Threshold> = cells(ps_row)
+        .map(|(k, p)| cell_threshold(p, ps_row, k))
+        .collect::<Result<_, _>>()?;
+
+    let declared_directions: Vec<Option<OptimizationDirection>> = match kinds_row {
+        Some(kinds_row) => cells(kinds_row)
+            .map(|(k, data_kind)| {
+                if data_kind.is_empty() {
+                    return Ok(None);
+                }
+                parse_kind(cell_string(data_kind, kinds_row, k)?).map_err(|message| {
+                    ParseError::InvalidCell {
+                        row: kinds_row,
+                        col: k,
+                        message,
+                    }
+                })
+            })
+            .collect::<Result<_, _>>()?,
+        None => vec![None; ncrits],
+    };
+
+    if alternatives.is_empty() {
+        return Err(ParseError::NoAlternatives);
+    }
+
     let alt_table = AlternativeTable::new(alternatives.into_boxed_slice())
         .with_criteria_names(criteria_names)
         .with_criteria_directions(criteria_directions);
+    alt_table
+        .validate_names()
+        .map_err(|e| ParseError::InvalidLayout(e.to_string()))?;
 
     for k in 0..ncrits {
-        pref_funs.push(generalized_criterion::from_params(
-            fun_types[k],
-            qs[k],
-            ps[k],
+        // "auto" thresholds, see `Threshold::Auto`
+        let auto_p = PrometheeProblem::auto_threshold(&alt_table.criterion(k).unwrap());
+        let (min, max) = alt_table.criterion_bounds(k).unwrap();
+        let resolve = |threshold: Threshold, auto: f64| match threshold {
+            Threshold::Value(val) => val,
+            Threshold::Auto => auto,
+            Threshold::SpanFraction(fraction) => fraction * (max - min),
+        };
+        let p = resolve(ps[k], auto_p);
+        let q = resolve(qs[k], AUTO_Q_RATIO * auto_p);
+        pref_funs.push(
+            generalized_criterion::try_from_params_localized(fun_types[k], None, q, p).map_err(
+                |message| ParseError::InvalidCell {
+                    row: functions_row,
+                    col: k + 1,
+                    message,
+                },
+            )?,
+        )
+    }
+
+    PrometheeProblem::try_new(alt_table, pref_funs, weights)
+        .map(|problem| problem.with_declared_directions(declared_directions))
+        .map_err(|e| match e {
+            PrometheeError::InvalidThresholds { criterion, message } => ParseError::InvalidCell {
+                row: ps_row,
+                col: criterion + 1,
+                message,
+            },
+            e => ParseError::InvalidLayout(e.to_string()),
+        })
+}
+
+/// Direction implied by a criterion kind: Min for a cost, Max for a benefit, None if no kind is given
+fn parse_kind(kind: &str) -> Result<Option<OptimizationDirection>, String> {
+    match kind.trim().to_lowercase().as_str() {
+        "" => Ok(None),
+        "cost" => Ok(Some(OptimizationDirection::Min)),
+        "benefit" => Ok(Some(OptimizationDirection::Max)),
+        _ => Err(format!(
+            "Invalid criterion kind '{}', expected 'cost' or 'benefit'",
+            kind
+        )),
+    }
+}
+
+/// Specification of a criterion in a JSON config, see `from_matrix_and_config`
+#[derive(Debug, Deserialize)]
+struct CriterionConfig {
+    name: String,
+    direction: String,
+    weight: f64,
+    function: String,
+    #[serde(default)]
+    q: f64,
+    #[serde(default)]
+    p: f64,
+    /// "cost" or "benefit", checked against the direction by `PrometheeProblem::check_direction_consistency`
+    #[serde(default)]
+    kind: Option<String>,
+}
+
+/// Build a problem from a matrix of evaluations (one row per alternative) and a JSON config describing the criteria,
+/// given in the same order as the columns of the matrix:
+///
+/// ```json
+/// [
+///     {"name": "Price", "direction": "min", "weight": 3, "function": "V-Shape", "p": 3},
+///     {"name": "Speed", "direction": "max", "weight": 7, "function": "Linear", "q": 1, "p": 3}
+/// ]
+/// ```
+///
+/// The thresholds `q` and `p` default to 0 and the function names accept the same values as in Excel files.
+/// A criterion may also declare its `"kind"`, "cost" or "benefit", see `PrometheeProblem::check_direction_consistency`.
+/// Alternatives are named a_1, a_2...
+pub fn from_matrix_and_config(
+    matrix: Vec<Vec<f64>>,
+    config_json: &str,
+) -> Result<PrometheeProblem, Box<dyn Error>> {
+    let config: Vec<CriterionConfig> = serde_json::from_str(config_json)?;
+
+    if config.is_empty() {
+        return Err(PrometheeError::NoCriteria.into());
+    }
+    if matrix.is_empty() {
+        return Err(ParseError::NoAlternatives.into());
+    }
+    if let Some((i, row)) = matrix
+        .iter()
+        .enumerate()
+        .find(|(_, row)| row.len() != config.len())
+    {
+        return Err(ParseError::InvalidLayout(format!(
+            "Invalid number of columns at row {}, {} expected, {} found",
+            i,
+            config.len(),
+            row.len()
         ))
+        .into());
     }
 
-    Ok(PrometheeProblem::new(alt_table, pref_funs, weights))
-}
\ No newline at end of file
+    let criteria_directions = config
+        .iter()
+        .map(|criterion| OptimizationDirection::from_str(&criterion.direction))
+        .collect::<Result<Vec<_>, _>>()?;
+    let pref_funs = config
+        .iter()
+        .map(|criterion| {
+            generalized_criterion::try_from_params_localized(
+                &criterion.function,
+                None,
+                criterion.q,
+                criterion.p,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let declared_directions = config
+        .iter()
+        .map(|criterion| parse_kind(criterion.kind.as_deref().unwrap_or("")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let weights = config.iter().map(|criterion| criterion.weight).collect();
+    let criteria_names = config.into_iter().map(|criterion| criterion.name).collect();
+
+    let alt_table = AlternativeTable::from_matrix(matrix)
+        .with_criteria_names(criteria_names)
+        .with_criteria_directions(criteria_directions);
+    alt_table.validate_names()?;
+
+    Ok(PrometheeProblem::try_new(alt_table, pref_funs, weights)?
+        .with_declared_directions(declared_directions))
+}
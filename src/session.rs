@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Promethee2Result, PrometheeProblem, UnicriterionFlows};
+
+/// Problem whose evaluations evolve over time, solved again at each step and keeping the history of the net flows.
+/// Only the criteria whose evaluations changed are computed again, the argsort cache being updated incrementally.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrometheeSession {
+    problem: PrometheeProblem,
+    unicriterion_flows: UnicriterionFlows,
+    /// Net flows after each step
+    history: Vec<Vec<f64>>,
+}
+
+impl PrometheeSession {
+    /// Start a session from the current evaluations of the problem, the history is empty until the first step
+    pub fn new(problem: PrometheeProblem) -> Self {
+        let unicriterion_flows = problem.compute_unicriterion_flows();
+        Self {
+            problem,
+            unicriterion_flows,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn problem(&self) -> &PrometheeProblem {
+        &self.problem
+    }
+
+    /// Apply the updates (criterion k, alternative i, new evaluation), solve the problem and record its net flows
+    pub fn step(&mut self, updates: &[(usize, usize, f64)]) -> Promethee2Result {
+        let mut changed = vec![false; self.problem.q()];
+        for &(k, i, val) in updates {
+            if k >= self.problem.q() || i >= self.problem.n() {
+                panic!(
+                    "Wrong update index, criterion {} and alternative {} given, {} criteria and {} alternatives",
+                    k,
+                    i,
+                    self.problem.q(),
+                    self.problem.n()
+                );
+            }
+            self.problem.set_eval(k, i, val);
+            changed[k] = true;
+        }
+
+        for k in (0..self.problem.q()).filter(|&k| changed[k]) {
+            let (positive_flows, negative_flows) = self.problem.unicriterion_flows(k).unwrap();
+            self.unicriterion_flows.unicrit_positive_flows[k] = positive_flows;
+            self.unicriterion_flows.unicrit_negative_flows[k] = negative_flows;
+        }
+
+        let result = self.unicriterion_flows.aggregate(&self.problem.weights);
+        self.history.push(result.net_flows());
+        result
+    }
+
+    /// Net flows of the alternatives after each step, in order
+    pub fn history(&self) -> &[Vec<f64>] {
+        &self.history
+    }
+
+    /// Net flows of alternative i after each step
+    pub fn trajectory(&self, i: usize) -> Vec<f64> {
+        self.history.iter().map(|net_flows| net_flows[i]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alternatives::AlternativeTable;
+    use crate::generalized_criterion::GeneralizedCriterion;
+
+    fn init_problem() -> PrometheeProblem {
+        PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![
+                vec![3.0, 1.0, 2.0],
+                vec![2.0, 4.0, 2.0],
+                vec![2.0, 3.0, 5.0],
+                vec![1.0, 2.0, 3.0],
+            ]),
+            vec![
+                GeneralizedCriterion::VShape { p: 3.0 },
+                GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+                GeneralizedCriterion::Usual,
+            ],
+            vec![3.0, 7.0, 2.0],
+        )
+    }
+
+    #[test]
+    fn steps_match_fresh_solves() {
+        let mut session = PrometheeSession::new(init_problem());
+        let steps: Vec<Vec<(usize, usize, f64)>> = vec![
+            vec![(0, 3, 4.0)],
+            vec![(1, 0, 5.0), (2, 1, 1.0)],
+            vec![],
+            vec![(0, 0, 0.5), (0, 3, 2.0)],
+        ];
+
+        let mut fresh = init_problem();
+        for updates in &steps {
+            let result = session.step(updates);
+            for &(k, i, val) in updates {
+                fresh.alt_table.set_performance(i, k, val);
+            }
+            fresh.rebuild_cache();
+
+            let expected = fresh.solve().net_flows();
+            result
+                .net_flows()
+                .iter()
+                .zip(&expected)
+                .for_each(|(a, b)| assert!((a - b).abs() < 1e-12));
+            assert!(session.problem().debug_verify_cache());
+        }
+
+        assert_eq!(session.history().len(), steps.len());
+        assert_eq!(session.history()[1], session.history()[2]);
+        assert_eq!(session.trajectory(3).len(), steps.len());
+    }
+}
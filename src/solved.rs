@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{FlowDispersion, Promethee2Result, PrometheeProblem, Ranking};
+
+/// Problem bundled with its solution, to answer repeated queries without solving it again
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SolvedProblem {
+    problem: PrometheeProblem,
+    result: Promethee2Result,
+}
+
+impl PrometheeProblem {
+    /// Solve the problem and freeze it, along with its result, into a read-only snapshot
+    pub fn into_solved(self) -> SolvedProblem {
+        let result = self.solve();
+        SolvedProblem {
+            problem: self,
+            result,
+        }
+    }
+}
+
+impl SolvedProblem {
+    pub fn problem(&self) -> &PrometheeProblem {
+        &self.problem
+    }
+
+    pub fn result(&self) -> &Promethee2Result {
+        &self.result
+    }
+
+    /// Give back the problem, e.g. to modify it and solve it again
+    pub fn into_problem(self) -> PrometheeProblem {
+        self.problem
+    }
+
+    /// Ranking of the alternatives with their names and net flows
+    pub fn ranking(&self) -> Ranking {
+        self.problem.ranking_table(&self.result)
+    }
+
+    pub fn ranked_alts(&self) -> Vec<usize> {
+        self.result.ranked_alts()
+    }
+
+    pub fn net_flows(&self) -> Vec<f64> {
+        self.result.net_flows()
+    }
+
+    pub fn net_flow(&self, ai: usize) -> Option<f64> {
+        self.result.net_flow(ai)
+    }
+
+    /// Unicriterion net flows of alternative ai, one per criterion
+    pub fn profile(&self, ai: usize) -> Option<Vec<f64>> {
+        (0..self.problem.q())
+            .map(|k| self.result.unicriterion_net_flow(k, ai))
+            .collect()
+    }
+
+    /// Spread of the weighted unicriterion net flows of each alternative, weighted by the weights of the problem
+    pub fn flow_dispersion(&self) -> Vec<FlowDispersion> {
+        self.result
+            .flow_dispersion_with_weights(&self.problem.weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alternatives::AlternativeTable;
+    use crate::generalized_criterion::GeneralizedCriterion;
+
+    fn init_problem() -> PrometheeProblem {
+        PrometheeProblem::new(
+            AlternativeTable::from_matrix(vec![vec![3.0, 1.0], vec![2.0, 4.0], vec![2.0, 3.0]]),
+            vec![
+                GeneralizedCriterion::VShape { p: 3.0 },
+                GeneralizedCriterion::Linear { q: 1.0, p: 3.0 },
+            ],
+            vec![3.0, 7.0],
+        )
+    }
+
+    #[test]
+    fn queries_match_result() {
+        let result = init_problem().solve();
+        let solved = init_problem().into_solved();
+
+        assert_eq!(solved.net_flows(), result.net_flows());
+        assert_eq!(solved.ranked_alts(), result.ranked_alts());
+        assert_eq!(solved.net_flow(3), None);
+        assert_eq!(
+            solved.profile(1).unwrap(),
+            vec![
+                result.unicriterion_net_flow(0, 1).unwrap(),
+                result.unicriterion_net_flow(1, 1).unwrap()
+            ]
+        );
+        assert_eq!(solved.profile(3), None);
+        assert_eq!(solved.ranking().indices(), result.ranked_alts());
+    }
+
+    #[test]
+    fn flow_dispersion_uses_problem_weights() {
+        let solved = init_problem().into_solved();
+        let dispersion = solved.flow_dispersion();
+
+        assert_eq!(
+            dispersion,
+            solved.result().flow_dispersion_with_weights(&[3.0, 7.0])
+        );
+        // The mean times q is the net flow of the alternative
+        for (d, net_flow) in dispersion.iter().zip(solved.net_flows()) {
+            assert!((d.mean * 2.0 - net_flow).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let solved = init_problem().into_solved();
+        let json = serde_json::to_string(&solved).unwrap();
+        let restored: SolvedProblem = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.ranking(), solved.ranking());
+        assert_eq!(restored.problem().q(), 2);
+    }
+}
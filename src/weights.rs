@@ -0,0 +1,51 @@
+/// Same weight for each of the q criteria
+pub fn equal(q: usize) -> Vec<f64> {
+    vec![1.0 / q as f64; q]
+}
+
+/// Rank-order centroid weights, from the indices of the criteria sorted from the most to the least
+/// important. The criterion at rank r (starting at 1) gets the weight `1/q * sum_{i=r}^{q} 1/i`.
+pub fn rank_order_centroid(ranking: &[usize]) -> Vec<f64> {
+    let q = ranking.len();
+    let mut weights = vec![f64::NAN; q];
+
+    for (r, &k) in ranking.iter().enumerate() {
+        if k >= q || !weights[k].is_nan() {
+            panic!(
+                "Invalid ranking of criteria, expected a permutation of 0..{}, got {:?}",
+                q, ranking
+            );
+        }
+        weights[k] = (r + 1..=q).map(|i| 1.0 / i as f64).sum::<f64>() / q as f64;
+    }
+
+    weights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_weights() {
+        assert_eq!(equal(4), vec![0.25; 4]);
+    }
+
+    #[test]
+    fn rank_order_centroid_q3() {
+        // The second criterion is the most important, then the third one and the first one
+        let weights = rank_order_centroid(&[1, 2, 0]);
+        let expected = [2.0 / 18.0, 11.0 / 18.0, 5.0 / 18.0];
+
+        for (w, e) in weights.iter().zip(expected) {
+            assert!((w - e).abs() < 1e-12);
+        }
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rank_order_centroid_not_a_permutation() {
+        rank_order_centroid(&[0, 0, 1]);
+    }
+}
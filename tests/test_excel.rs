@@ -1,4 +1,6 @@
-use rs_promethee_core::{parse, generalized_criterion::GeneralizedCriterion, PrometheeProblem};
+#![cfg(feature = "parse")]
+
+use rs_promethee_core::{generalized_criterion::GeneralizedCriterion, parse};
 
 #[test]
 pub fn read_correct_excel() {
@@ -40,6 +42,201 @@ pub fn read_correct_excel() {
 
             (0..problem.q()).for_each(|k| assert_eq!(*problem.pref_fun(k).unwrap(), pref_funs[k]));
         }
-        Err(e) => assert!(false, "Should read excel file, error: {:?}", e),
+        Err(e) => panic!("Should read excel file, error: {:?}", e),
+    }
+}
+
+#[test]
+pub fn try_from_file_never_panics_on_garbage() {
+    let dir = std::env::temp_dir();
+
+    // Simple linear congruential generator to produce reproducible random bytes
+    let mut state: u64 = 12345;
+    let mut next_byte = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 56) as u8
+    };
+
+    for (i, len) in [0, 1, 16, 512, 4096].into_iter().enumerate() {
+        let bytes: Vec<u8> = (0..len).map(|_| next_byte()).collect();
+        let file_path = dir.join(format!("rs_promethee_fuzz_{}.xlsx", i));
+        std::fs::write(&file_path, &bytes).unwrap();
+
+        let res = std::panic::catch_unwind(|| parse::try_from_file(&file_path));
+        std::fs::remove_file(&file_path).unwrap();
+
+        assert!(matches!(res, Ok(Err(_))), "Should fail without panicking");
     }
 }
+
+#[test]
+pub fn try_from_file_errors() {
+    let project_path = env!("CARGO_MANIFEST_DIR");
+
+    let missing = parse::try_from_file(format!("{}/tests/files/missing.xlsx", project_path));
+    assert!(matches!(missing, Err(parse::ParseError::Workbook(_))));
+
+    let correct = parse::try_from_file(format!("{}/tests/files/test_correct.xlsx", project_path));
+    assert!(correct.is_ok());
+}
+
+#[test]
+pub fn read_all_sheets() {
+    let project_path = env!("CARGO_MANIFEST_DIR");
+    let file_path = format!("{}/tests/files/test_multi_sheet.xlsx", project_path);
+
+    let problems = parse::from_excel_all(&file_path).unwrap();
+
+    // The "Notes" sheet is skipped
+    let names: Vec<&str> = problems.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["Cars", "Suppliers"]);
+
+    let (_, cars) = &problems[0];
+    assert_eq!(cars.n(), 3);
+    assert_eq!(cars.q(), 3);
+    assert_eq!(*cars.perf(1, 1).unwrap(), 290.0);
+
+    let (_, suppliers) = &problems[1];
+    assert_eq!(suppliers.n(), 4);
+    assert_eq!(suppliers.q(), 2);
+    assert_eq!(*suppliers.pref_fun(0).unwrap(), GeneralizedCriterion::Usual);
+    assert_eq!(
+        *suppliers.pref_fun(1).unwrap(),
+        GeneralizedCriterion::VShape { p: 5.0 }
+    );
+}
+
+#[test]
+pub fn read_auto_thresholds() {
+    let project_path = env!("CARGO_MANIFEST_DIR");
+    let file_path = format!("{}/tests/files/test_auto_thresholds.xlsx", project_path);
+
+    let problem = parse::from_excel(&file_path).unwrap();
+
+    // Interquartile range of the first criterion, range of the second since its IQR is zero
+    assert_eq!(
+        *problem.pref_fun(0).unwrap(),
+        GeneralizedCriterion::VShape { p: 20.0 }
+    );
+    assert_eq!(
+        *problem.pref_fun(1).unwrap(),
+        GeneralizedCriterion::Linear { q: 25.0, p: 100.0 }
+    );
+    assert_eq!(
+        *problem.pref_fun(2).unwrap(),
+        GeneralizedCriterion::Linear { q: 0.1, p: 0.3 }
+    );
+}
+
+#[test]
+pub fn read_span_percentage_thresholds() {
+    let project_path = env!("CARGO_MANIFEST_DIR");
+    let file_path = format!("{}/tests/files/test_span_percentages.xlsx", project_path);
+
+    let problem = parse::from_excel(&file_path).unwrap();
+
+    // Spans are 50 for the first criterion and 200 for the second one
+    assert_eq!(
+        *problem.pref_fun(0).unwrap(),
+        GeneralizedCriterion::VShape { p: 0.2 * 50.0 }
+    );
+    assert_eq!(
+        *problem.pref_fun(1).unwrap(),
+        GeneralizedCriterion::Linear {
+            q: 0.05 * 200.0,
+            p: 0.5 * 200.0
+        }
+    );
+}
+
+#[test]
+pub fn read_matrix_and_config() {
+    let project_path = env!("CARGO_MANIFEST_DIR");
+    let file_path = format!("{}/tests/files/test_config.json", project_path);
+    let config = std::fs::read_to_string(file_path).unwrap();
+    let matrix = vec![vec![3.0, 1.0], vec![2.0, 4.0], vec![2.0, 3.0]];
+
+    let problem = parse::from_matrix_and_config(matrix.clone(), &config).unwrap();
+    assert_eq!(problem.criteria_names()[0].as_ref(), "Price");
+    assert_eq!(
+        *problem.pref_fun(1).unwrap(),
+        GeneralizedCriterion::Linear { q: 1.0, p: 3.0 }
+    );
+    assert_eq!(problem.w(0), Some(&0.3));
+
+    // The config declares two criteria, the matrix must have two columns
+    let narrow = vec![vec![3.0], vec![2.0]];
+    assert!(parse::from_matrix_and_config(narrow, &config).is_err());
+    assert!(parse::from_matrix_and_config(matrix, "[{\"name\": \"Price\"}]").is_err());
+}
+
+#[test]
+pub fn read_config_kinds() {
+    let project_path = env!("CARGO_MANIFEST_DIR");
+    let file_path = format!("{}/tests/files/test_config.json", project_path);
+    let config = std::fs::read_to_string(file_path).unwrap();
+    let matrix = vec![vec![3.0, 1.0], vec![2.0, 4.0], vec![2.0, 3.0]];
+
+    let problem = parse::from_matrix_and_config(matrix.clone(), &config).unwrap();
+    assert_eq!(problem.check_direction_consistency(), Ok(()));
+
+    // A price declared as a cost but maximized
+    let mismatched = config.replacen(
+        "\"direction\": \"min\"",
+        "\"direction\": \"max\", \"kind\": \"cost\"",
+        1,
+    );
+    let problem = parse::from_matrix_and_config(matrix, &mismatched).unwrap();
+    assert_eq!(problem.check_direction_consistency(), Err(vec![0]));
+}
+
+#[test]
+pub fn read_labeled_rows() {
+    let project_path = env!("CARGO_MANIFEST_DIR");
+    let file_path = format!("{}/tests/files/test_blank_rows.xlsx", project_path);
+
+    // Blank rows are skipped and the metadata rows are found by their label, whatever their order
+    let problem = parse::from_excel(&file_path).unwrap();
+    assert_eq!(problem.n(), 2);
+    assert_eq!(problem.alt_names(), vec!["Clio", "Model 3"]);
+    assert_eq!(problem.w(0), Some(&(2.0 / 3.0)));
+    assert_eq!(
+        *problem.pref_fun(1).unwrap(),
+        GeneralizedCriterion::Linear { q: 10.0, p: 30.0 }
+    );
+    assert_eq!(*problem.perf(0, 1).unwrap(), 40000.0);
+
+    let file_path = format!("{}/tests/files/test_missing_row.xlsx", project_path);
+    assert_eq!(
+        parse::try_from_file(&file_path).unwrap_err(),
+        parse::ParseError::MissingRow("p".to_string())
+    );
+}
+
+#[test]
+pub fn read_alternatives_named_like_metadata() {
+    let project_path = env!("CARGO_MANIFEST_DIR");
+    let file_path = format!("{}/tests/files/test_metadata_names.xlsx", project_path);
+
+    // Labels are only metadata before the first alternative, "p" being the last metadata row
+    let problem = parse::from_excel(&file_path).unwrap();
+    assert_eq!(problem.alt_names(), vec!["p", "Type", "Weight"]);
+    assert_eq!(
+        *problem.pref_fun(0).unwrap(),
+        GeneralizedCriterion::VShape { p: 1000.0 }
+    );
+    assert_eq!(*problem.perf(1, 0).unwrap(), 110.0);
+}
+
+#[test]
+pub fn read_criteria_kinds() {
+    let project_path = env!("CARGO_MANIFEST_DIR");
+    let file_path = format!("{}/tests/files/test_kinds.xlsx", project_path);
+
+    // The price is declared as a cost but maximized, the robustness has no declared kind
+    let problem = parse::from_excel(&file_path).unwrap();
+    assert_eq!(problem.n(), 3);
+    assert_eq!(problem.check_direction_consistency(), Err(vec![0]));
+}